@@ -0,0 +1,139 @@
+//! Orderbook read/write cost: `RwLock<Orderbook>` vs lock-free `SharedBook`.
+//!
+//! Run with `cargo bench --bench book_snapshot`. The contended cases keep a
+//! writer thread publishing updates while the detection-style read is timed.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Instant;
+
+use prediction_market_arbitrage_mvp::types::{
+    BookStamp, Contracts, Orderbook, Price, PriceLevel, SharedBook, Side,
+};
+
+/// Ten-level ladder like a typical Kalshi book
+fn ladder(best: u32) -> Vec<PriceLevel> {
+    (0..10)
+        .map(|i| PriceLevel {
+            price: Price::from_cents(best + i),
+            size: Contracts(100),
+        })
+        .collect()
+}
+
+fn stamp() -> BookStamp {
+    BookStamp {
+        exchange_ts: None,
+        received_at: Instant::now(),
+    }
+}
+
+fn seeded() -> Orderbook {
+    let mut book = Orderbook::default();
+    book.set_asks(Side::Yes, ladder(40));
+    book.set_asks(Side::No, ladder(55));
+    book
+}
+
+/// What detection reads from each book
+fn top_of_book(book: &Orderbook) -> u64 {
+    book.yes_ask.units() as u64
+        + book.no_ask.units() as u64
+        + book.yes_size.get()
+        + book.no_size.get()
+}
+
+/// Run `read` while a second thread keeps calling `write`
+fn contended<W, R>(c: &mut Criterion, name: &str, write: W, read: R)
+where
+    W: Fn(u32) + Send + 'static,
+    R: Fn() -> u64,
+{
+    let running = Arc::new(AtomicBool::new(true));
+    let writer = {
+        let running = running.clone();
+        thread::spawn(move || {
+            let mut tick = 0u32;
+            while running.load(Ordering::Relaxed) {
+                write(30 + tick % 20);
+                tick = tick.wrapping_add(1);
+            }
+        })
+    };
+
+    c.bench_function(name, |b| b.iter(|| black_box(read())));
+
+    running.store(false, Ordering::Relaxed);
+    writer.join().unwrap();
+}
+
+fn bench_reads(c: &mut Criterion) {
+    let locked = Arc::new(RwLock::new(seeded()));
+    let shared = Arc::new(SharedBook::default());
+    shared.update(|book| *book = seeded());
+
+    c.bench_function("read/rwlock", |b| {
+        b.iter(|| black_box(top_of_book(&locked.read().unwrap())))
+    });
+    c.bench_function("read/shared_book", |b| {
+        b.iter(|| black_box(top_of_book(&shared.load())))
+    });
+
+    let writer_book = locked.clone();
+    let reader_book = locked.clone();
+    contended(
+        c,
+        "read_contended/rwlock",
+        move |price| {
+            let mut book = writer_book.write().unwrap();
+            book.set_asks(Side::Yes, ladder(price));
+            book.set_stamp(Side::Yes, stamp());
+        },
+        move || top_of_book(&reader_book.read().unwrap()),
+    );
+
+    let writer_book = shared.clone();
+    let reader_book = shared.clone();
+    contended(
+        c,
+        "read_contended/shared_book",
+        move |price| {
+            let levels = ladder(price);
+            let stamp = stamp();
+            writer_book.update(|book| {
+                book.set_asks(Side::Yes, levels.clone());
+                book.set_stamp(Side::Yes, stamp);
+            });
+        },
+        move || top_of_book(&reader_book.load()),
+    );
+}
+
+fn bench_writes(c: &mut Criterion) {
+    let locked = RwLock::new(seeded());
+    let shared = SharedBook::default();
+    shared.update(|book| *book = seeded());
+
+    c.bench_function("write/rwlock", |b| {
+        b.iter(|| {
+            let mut book = locked.write().unwrap();
+            book.set_asks(Side::Yes, ladder(black_box(41)));
+            book.set_stamp(Side::Yes, stamp());
+        })
+    });
+    c.bench_function("write/shared_book", |b| {
+        b.iter(|| {
+            let levels = ladder(black_box(41));
+            let stamp = stamp();
+            shared.update(|book| {
+                book.set_asks(Side::Yes, levels.clone());
+                book.set_stamp(Side::Yes, stamp);
+            });
+        })
+    });
+}
+
+criterion_group!(benches, bench_reads, bench_writes);
+criterion_main!(benches);
@@ -0,0 +1,293 @@
+//! Multi-outcome (neg-risk) event basket detection.
+//!
+//! Exactly one outcome of an exhaustive [`EventGroup`] resolves YES, so a YES
+//! on every outcome pays $1.00 and a NO on every outcome pays $(N - 1). Each
+//! outcome's leg is bought on whichever venue is cheapest after fees, so a
+//! basket can mix Kalshi and Polymarket markets.
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::debug;
+
+use crate::edge::{EdgeReturn, MinEdge, MinEdgeDefaults};
+use crate::execution::{check_freshness, DetectionStats, FreshnessLimits, Staleness};
+use crate::fees::FeeModel;
+use crate::types::{
+    BasketLeg, BasketOpportunity, BookStamp, Contracts, EventGroup, MarketState, Notional, Price,
+    Side, Venue,
+};
+
+/// Cheapest fresh quote for one outcome
+struct Quote {
+    market: Arc<MarketState>,
+    venue: Venue,
+    price: Price,
+    size: Contracts,
+    stamp: BookStamp,
+}
+
+/// Best venue to buy `side` of `market` on, skipping empty books.
+/// `Err` if the only quotes are too old to trade on.
+fn best_quote(
+    market: &Arc<MarketState>,
+    side: Side,
+    limits: &FreshnessLimits,
+    now: Instant,
+) -> Result<Option<Quote>, Staleness> {
+    let mut stale = false;
+    let best = [Venue::Kalshi, Venue::Polymarket]
+        .into_iter()
+        .filter_map(|venue| {
+            let book = market.book(venue).load();
+            let top = book.asks(side).first().copied()?;
+            if top.price.is_zero() || top.size.is_zero() {
+                return None;
+            }
+            let stamp = limits.confirm(venue, book.stamp(side))?;
+            if stamp.age(now) > limits.max_age {
+                stale = true;
+                return None;
+            }
+            Some(Quote {
+                market: market.clone(),
+                venue,
+                price: top.price,
+                size: top.size,
+                stamp,
+            })
+        })
+        .min_by_key(|q| q.price + q.market.pair.fees.venue(q.venue).unit_fee(q.price));
+    match best {
+        None if stale => Err(Staleness::Stale),
+        best => Ok(best),
+    }
+}
+
+/// What one basket of `legs` outcomes pays on `side`
+fn basket_payout(side: Side, legs: usize) -> Option<Price> {
+    let winners = match side {
+        Side::Yes => 1,
+        Side::No => legs.checked_sub(1)?,
+    };
+    let units = u32::try_from(winners).ok()?.checked_mul(Price::SCALE)?;
+    Some(Price(units))
+}
+
+/// Detect a `side` basket over every outcome of `group`.
+///
+/// `markets` holds the state of each outcome, in `group.market_ids` order.
+/// `Err` if stale or skewed legs left the basket unjudged.
+pub fn detect_basket(
+    group: &EventGroup,
+    markets: &[Arc<MarketState>],
+    side: Side,
+    limits: &FreshnessLimits,
+    min_edges: &MinEdgeDefaults,
+    stats: &DetectionStats,
+) -> Result<Option<BasketOpportunity>, Staleness> {
+    if markets.len() < 2 || (side == Side::Yes && !group.exhaustive) {
+        return Ok(None);
+    }
+    let now = Instant::now();
+
+    let mut quotes = Vec::with_capacity(markets.len());
+    for market in markets {
+        match best_quote(market, side, limits, now)? {
+            Some(quote) => quotes.push(quote),
+            None => return Ok(None),
+        }
+    }
+
+    // Every leg must come from the same moment, like the two legs of a pair
+    let oldest = quotes.iter().map(|q| q.stamp).min_by_key(|s| s.received_at);
+    let newest = quotes.iter().map(|q| q.stamp).max_by_key(|s| s.received_at);
+    if let Err(Staleness::Skewed) = check_freshness(oldest, newest, limits, now) {
+        stats.skewed_legs.fetch_add(1, Ordering::Relaxed);
+        debug!(
+            "[ARB] Skipping {} {} basket: legs skewed",
+            group.description, side
+        );
+        return Err(Staleness::Skewed);
+    }
+
+    Ok(price_basket(group, markets, side, quotes, min_edges))
+}
+
+/// Price a basket from one fresh quote per outcome, if it clears the
+/// minimum edge
+fn price_basket(
+    group: &EventGroup,
+    markets: &[Arc<MarketState>],
+    side: Side,
+    quotes: Vec<Quote>,
+    min_edges: &MinEdgeDefaults,
+) -> Option<BasketOpportunity> {
+    let quantity = quotes.iter().map(|q| q.size).min()?;
+    let payout = quantity.notional(basket_payout(side, quotes.len())?)?;
+
+    let mut cost = Notional::ZERO;
+    let mut legs = Vec::with_capacity(quotes.len());
+    for quote in quotes {
+        let pair = &quote.market.pair;
+        let fee = pair
+            .fees
+            .venue(quote.venue)
+            .order_fee(quote.price, quantity);
+        cost = cost
+            .checked_add(quantity.notional(quote.price)?)?
+            .checked_add(fee)?;
+        legs.push(BasketLeg {
+            market_id: pair.id.clone(),
+            leg: pair.leg(quote.venue, side),
+            price: quote.price,
+            fee,
+        });
+    }
+
+    let expected_profit = payout.checked_sub(cost)?;
+    if expected_profit <= Notional::ZERO {
+        return None;
+    }
+
+    // Capital is tied up until the last outcome settles
+    let utc_now = chrono::Utc::now();
+    let settles_in = markets
+        .iter()
+        .map(|m| m.pair.settles_in(utc_now))
+        .collect::<Option<Vec<_>>>()
+        .and_then(|all| all.into_iter().max());
+    let per_basket = |total: Notional| Notional(total.0 / quantity.get() as i64);
+    let edge_return = EdgeReturn::new(per_basket(expected_profit), per_basket(cost), settles_in);
+    let min_edge = markets
+        .iter()
+        .map(|m| m.pair.edge_threshold(min_edges))
+        .reduce(MinEdge::strictest)?;
+    if !min_edge.accepts(&edge_return) {
+        debug!(
+            "[ARB] Skipping {} {} basket: edge {} below minimum",
+            group.description, side, edge_return
+        );
+        return None;
+    }
+
+    Some(BasketOpportunity {
+        event_id: group.id.clone(),
+        description: group.description.clone(),
+        side,
+        legs,
+        quantity,
+        cost,
+        payout,
+        expected_profit,
+        edge_return,
+        timestamp: chrono::Utc::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::get_hardcoded_markets;
+    use crate::supervisor::FeedLiveness;
+    use crate::types::{PriceLevel, SharedBook};
+    use std::time::Duration;
+
+    fn quote(book: &SharedBook, side: Side, cents: u32, size: u64) {
+        let stamp = BookStamp {
+            exchange_ts: None,
+            received_at: Instant::now(),
+        };
+        book.update(|book| {
+            let level = PriceLevel {
+                price: Price::from_cents(cents),
+                size: Contracts(size),
+            };
+            book.set_asks(side, vec![level]);
+            book.set_stamp(side, stamp);
+        });
+    }
+
+    fn outcomes(n: usize) -> (EventGroup, Vec<Arc<MarketState>>) {
+        let markets: Vec<Arc<MarketState>> = (0..n)
+            .map(|i| {
+                let mut pair = get_hardcoded_markets().remove(0);
+                pair.id = format!("outcome-{}", i);
+                Arc::new(MarketState::new(pair))
+            })
+            .collect();
+        let group = EventGroup {
+            id: "event".into(),
+            description: "Test event".into(),
+            market_ids: markets.iter().map(|m| m.pair.id.clone()).collect(),
+            exhaustive: true,
+        };
+        (group, markets)
+    }
+
+    fn limits() -> FreshnessLimits {
+        FreshnessLimits {
+            max_age: Duration::from_secs(2),
+            max_skew: Duration::from_secs(1),
+            feeds: FeedLiveness::default(),
+        }
+    }
+
+    #[test]
+    fn test_yes_basket_mixes_venues() {
+        let (mut group, markets) = outcomes(3);
+        let min_edges = MinEdgeDefaults::from_config();
+        let stats = DetectionStats::new();
+
+        // Cheapest YES per outcome: Poly 30¢, Kalshi 31¢, Poly 33¢ = 94¢ + Kalshi fee
+        quote(&markets[0].kalshi, Side::Yes, 35, 50);
+        quote(&markets[0].poly, Side::Yes, 30, 20);
+        quote(&markets[1].kalshi, Side::Yes, 31, 40);
+        quote(&markets[1].poly, Side::Yes, 34, 100);
+        quote(&markets[2].poly, Side::Yes, 33, 60);
+
+        let basket = detect_basket(&group, &markets, Side::Yes, &limits(), &min_edges, &stats)
+            .unwrap()
+            .unwrap();
+        let venues: Vec<Venue> = basket.legs.iter().map(|l| l.leg.venue).collect();
+        assert_eq!(
+            venues,
+            vec![Venue::Polymarket, Venue::Kalshi, Venue::Polymarket]
+        );
+        assert_eq!(basket.quantity, Contracts(20));
+        // 20 × 31¢ on Kalshi: 0.07 × 0.31 × 0.69 × 20 = $0.2995 -> $0.30
+        assert_eq!(basket.legs[1].fee, Notional::from_cents(30));
+        assert_eq!(basket.expected_profit, Notional::from_cents(20 * 6 - 30));
+
+        // Without every outcome listed, the YES basket may pay nothing
+        group.exhaustive = false;
+        assert!(matches!(
+            detect_basket(&group, &markets, Side::Yes, &limits(), &min_edges, &stats),
+            Ok(None)
+        ));
+    }
+
+    #[test]
+    fn test_no_basket_pays_all_but_one() {
+        let (group, markets) = outcomes(3);
+        let min_edges = MinEdgeDefaults::from_config();
+        let stats = DetectionStats::new();
+
+        // NO on all three pays $2.00; 60 + 65 + 70 = $1.95 on Polymarket
+        for (market, cents) in markets.iter().zip([60, 65, 70]) {
+            quote(&market.poly, Side::No, cents, 10);
+        }
+        let basket = detect_basket(&group, &markets, Side::No, &limits(), &min_edges, &stats)
+            .unwrap()
+            .unwrap();
+        assert_eq!(basket.payout, Notional::from_cents(2000));
+        assert_eq!(basket.expected_profit, Notional::from_cents(50));
+
+        // An outcome with no quote leaves the basket incomplete
+        markets[2].poly.clear();
+        assert!(matches!(
+            detect_basket(&group, &markets, Side::No, &limits(), &min_edges, &stats),
+            Ok(None)
+        ));
+    }
+}
@@ -1,12 +1,130 @@
 //! Configuration and hardcoded market definitions for MVP.
 
-use crate::types::{MarketPair, MarketType};
+use crate::edge::MinEdge;
+use crate::fees::{FeeSchedule, PairFees, PolymarketFee};
+use crate::lines::Line;
+use crate::types::{
+    Contracts, EventGroup, MarketPair, MarketType, Notional, Orientation, Price, Venue,
+};
+use std::time::Duration;
 
-/// Arbitrage threshold in cents (100 = $1.00)
-pub const ARB_THRESHOLD_CENTS: u16 = 100;
+/// What a YES + NO pair pays at settlement. Opportunities must also clear
+/// their market's minimum edge (see [`min_edge`]).
+pub const PAIR_PAYOUT: Price = Price::ONE;
 
-/// WebSocket reconnect delay in seconds
-pub const WS_RECONNECT_DELAY_SECS: u64 = 5;
+/// Full detection sweep interval in milliseconds (book updates trigger
+/// detection immediately; the sweep is a safety net)
+pub const DETECTION_SWEEP_INTERVAL_MS: u64 = 5_000;
+
+/// Initial WebSocket reconnect delay in milliseconds (doubles per failure)
+pub const WS_RECONNECT_BASE_DELAY_MS: u64 = 500;
+
+/// Upper bound on the WebSocket reconnect delay in seconds
+pub const WS_RECONNECT_MAX_DELAY_SECS: u64 = 60;
+
+/// Minimum reconnect delay after an HTTP 429 without Retry-After, in seconds
+pub const WS_RATE_LIMIT_DELAY_SECS: u64 = 30;
+
+/// Consecutive reconnect failures before a feed is reported down
+pub const WS_RECONNECT_ALERT_ATTEMPTS: u32 = 10;
+
+/// Application-level liveness settings for one venue's feed connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Liveness {
+    /// How often the client pings the server
+    pub ping_interval: Duration,
+    /// Reconnect if no frame at all arrives for this long
+    pub read_idle_timeout: Duration,
+    /// Reconnect if a market that has been updating goes silent this long
+    pub market_idle_timeout: Duration,
+}
+
+/// Liveness settings per venue.
+/// Kalshi pings clients every 10s; Polymarket expects a client PING every 10s.
+pub fn liveness(venue: Venue) -> Liveness {
+    match venue {
+        Venue::Kalshi => Liveness {
+            ping_interval: Duration::from_secs(10),
+            read_idle_timeout: Duration::from_secs(30),
+            market_idle_timeout: Duration::from_secs(600),
+        },
+        Venue::Polymarket => Liveness {
+            ping_interval: Duration::from_secs(10),
+            read_idle_timeout: Duration::from_secs(30),
+            market_idle_timeout: Duration::from_secs(600),
+        },
+    }
+}
+
+/// Venue environment profile, selecting the default endpoint set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Environment {
+    /// Kalshi demo exchange, public Polymarket feed
+    Demo,
+    /// Production exchanges
+    Prod,
+    /// Local stand-in servers (integration tests, mock exchanges)
+    Local,
+}
+
+impl std::str::FromStr for Environment {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "demo" => Ok(Environment::Demo),
+            "prod" | "production" => Ok(Environment::Prod),
+            "local" => Ok(Environment::Local),
+            other => anyhow::bail!("Unknown environment: {}", other),
+        }
+    }
+}
+
+impl std::fmt::Display for Environment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Environment::Demo => write!(f, "demo"),
+            Environment::Prod => write!(f, "prod"),
+            Environment::Local => write!(f, "local"),
+        }
+    }
+}
+
+/// WebSocket and REST endpoints for every venue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    pub kalshi_ws: String,
+    pub kalshi_rest: String,
+    pub poly_ws: String,
+    pub poly_rest: String,
+}
+
+impl Endpoints {
+    /// Default endpoints for an environment profile
+    pub fn for_environment(env: Environment) -> Self {
+        match env {
+            Environment::Demo => Self {
+                kalshi_ws: "wss://demo-api.kalshi.co/trade-api/ws/v2".into(),
+                kalshi_rest: "https://demo-api.kalshi.co/trade-api/v2".into(),
+                // Polymarket has no demo exchange; its public feed is read-only
+                poly_ws: "wss://ws-subscriptions-clob.polymarket.com/ws/market".into(),
+                poly_rest: "https://clob.polymarket.com".into(),
+            },
+            Environment::Prod => Self {
+                kalshi_ws: "wss://api.elections.kalshi.com/trade-api/ws/v2".into(),
+                kalshi_rest: "https://api.elections.kalshi.com/trade-api/v2".into(),
+                poly_ws: "wss://ws-subscriptions-clob.polymarket.com/ws/market".into(),
+                poly_rest: "https://clob.polymarket.com".into(),
+            },
+            Environment::Local => Self {
+                kalshi_ws: "ws://127.0.0.1:8081/trade-api/ws/v2".into(),
+                kalshi_rest: "http://127.0.0.1:8081/trade-api/v2".into(),
+                poly_ws: "ws://127.0.0.1:8082/ws/market".into(),
+                poly_rest: "http://127.0.0.1:8082".into(),
+            },
+        }
+    }
+}
 
 /// Hardcoded market list for MVP
 /// In production, this would be dynamically discovered
@@ -20,6 +138,12 @@ pub fn get_hardcoded_markets() -> Vec<MarketPair> {
             poly_slug: "chelsea-vs-arsenal".into(),
             poly_yes_token: "0x123...abc".into(), // Placeholder
             poly_no_token: "0x456...def".into(),  // Placeholder
+            orientation: Orientation::Same,
+            kalshi_line: None,
+            poly_line: None,
+            fees: PairFees::default(),
+            settles_at: None, // Placeholder: load from the venue
+            min_edge: None,
         },
         MarketPair {
             id: "lakers-celtics".into(),
@@ -29,6 +153,12 @@ pub fn get_hardcoded_markets() -> Vec<MarketPair> {
             poly_slug: "lakers-vs-celtics".into(),
             poly_yes_token: "0x789...ghi".into(), // Placeholder
             poly_no_token: "0xabc...jkl".into(),  // Placeholder
+            orientation: Orientation::Same,
+            kalshi_line: None,
+            poly_line: None,
+            fees: PairFees::default(),
+            settles_at: None, // Placeholder: load from the venue
+            min_edge: None,
         },
         MarketPair {
             id: "bitcoin-100k".into(),
@@ -38,29 +168,213 @@ pub fn get_hardcoded_markets() -> Vec<MarketPair> {
             poly_slug: "bitcoin-100k-feb-2025".into(),
             poly_yes_token: "0xdef...mno".into(), // Placeholder
             poly_no_token: "0xghi...pqr".into(),  // Placeholder
+            orientation: Orientation::Same,
+            // Strike in dollars
+            kalshi_line: Some(Line::from_points(100_000.0)),
+            poly_line: Some(Line::from_points(100_000.0)),
+            // Crypto price markets are fee-enabled on Polymarket
+            fees: PairFees {
+                poly: FeeSchedule::Polymarket(PolymarketFee { fee_rate_bps: 100 }),
+                ..PairFees::default()
+            },
+            settles_at: None, // Placeholder: load from the venue
+            min_edge: None,
         },
+        fed_outcome("cut", "Cut", "C25", "0xfed...c01", "0xfed...c02"),
+        fed_outcome("hold", "Hold", "H0", "0xfed...h01", "0xfed...h02"),
+        fed_outcome("hike", "Hike", "H25", "0xfed...k01", "0xfed...k02"),
     ]
 }
 
+/// One outcome of the March 2025 FOMC decision
+fn fed_outcome(id: &str, name: &str, strike: &str, yes: &str, no: &str) -> MarketPair {
+    MarketPair {
+        id: format!("fed-mar25-{}", id),
+        description: format!("Fed decision Mar 2025: {}", name),
+        market_type: MarketType::Outcome,
+        kalshi_ticker: format!("KXFEDDECISION-25MAR-{}", strike),
+        poly_slug: format!("fed-decision-in-march-{}", id),
+        poly_yes_token: yes.into(), // Placeholder
+        poly_no_token: no.into(),   // Placeholder
+        orientation: Orientation::Same,
+        kalshi_line: None,
+        poly_line: None,
+        fees: PairFees::default(),
+        settles_at: None, // Placeholder: load from the venue
+        min_edge: None,
+    }
+}
+
+/// Hardcoded multi-outcome events over the markets above
+pub fn get_hardcoded_events() -> Vec<EventGroup> {
+    vec![EventGroup {
+        id: "fed-mar25".into(),
+        description: "Fed decision Mar 2025".into(),
+        market_ids: ["cut", "hold", "hike"]
+            .iter()
+            .map(|id| format!("fed-mar25-{}", id))
+            .collect(),
+        exhaustive: true,
+    }]
+}
+
+/// Default minimum edge for a market type.
+/// Each bound can be overridden for every type with `MIN_EDGE_CENTS`,
+/// `MIN_RETURN_BPS` and `MIN_ANNUALIZED_BPS`.
+pub fn min_edge(market_type: MarketType) -> MinEdge {
+    let defaults = match market_type {
+        MarketType::Moneyline | MarketType::Spread | MarketType::Total => MinEdge {
+            profit: Notional::from_cents(1),
+            return_bps: 50,
+            annualized_bps: 1_000,
+        },
+        // Baskets have more legs to fill, so ask for more
+        MarketType::Outcome => MinEdge {
+            profit: Notional::from_cents(2),
+            return_bps: 100,
+            annualized_bps: 1_000,
+        },
+    };
+    let var = |name: &str| std::env::var(name).ok().and_then(|s| s.parse::<i64>().ok());
+    MinEdge {
+        profit: var("MIN_EDGE_CENTS")
+            .map(Notional::from_cents)
+            .unwrap_or(defaults.profit),
+        return_bps: var("MIN_RETURN_BPS").unwrap_or(defaults.return_bps),
+        annualized_bps: var("MIN_ANNUALIZED_BPS").unwrap_or(defaults.annualized_bps),
+    }
+}
+
 /// Get max position size from environment (default: 10 contracts)
-pub fn max_position_size() -> u16 {
+pub fn max_position_size() -> Contracts {
     std::env::var("MAX_POSITION_SIZE")
         .ok()
         .and_then(|s| s.parse().ok())
-        .unwrap_or(10)
+        .map(Contracts)
+        .unwrap_or(Contracts(10))
 }
 
 /// Get max daily loss in cents from environment (default: $50.00)
-pub fn max_daily_loss_cents() -> u16 {
+pub fn max_daily_loss_cents() -> Notional {
     std::env::var("MAX_DAILY_LOSS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map(Notional::from_cents)
+        .unwrap_or(Notional::from_cents(5000))
+}
+
+/// Get max quote age in milliseconds from environment (default: 2s).
+/// Legs not confirmed by their feed (an update or any later frame on the
+/// connection) for longer than this are not traded on.
+pub fn max_quote_age_ms() -> u64 {
+    std::env::var("MAX_QUOTE_AGE_MS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(2000)
+}
+
+/// Get max timestamp distance between an opportunity's two legs in
+/// milliseconds from environment (default: 1s)
+pub fn max_leg_skew_ms() -> u64 {
+    std::env::var("MAX_LEG_SKEW_MS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(1000)
+}
+
+/// Get how long to wait for an order's fills in milliseconds from
+/// environment (default: 5s). Orders that do not answer count as unfilled.
+pub fn order_timeout_ms() -> u64 {
+    std::env::var("ORDER_TIMEOUT_MS")
         .ok()
         .and_then(|s| s.parse().ok())
         .unwrap_or(5000)
 }
 
+/// Get how far over its original limit a failed leg may be retried, in
+/// cents, from environment (default: 1¢)
+pub fn unwind_retry_tolerance_cents() -> u32 {
+    std::env::var("UNWIND_RETRY_TOLERANCE_CENTS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(1)
+}
+
+/// Get how many times a failed leg is retried before selling back the
+/// filled one, from environment (default: 1)
+pub fn unwind_max_retries() -> u32 {
+    std::env::var("UNWIND_MAX_RETRIES")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(1)
+}
+
+/// Get how far under its average cost a naked leg may be sold back, in
+/// cents, from environment (default: 3¢)
+pub fn unwind_sell_tolerance_cents() -> u32 {
+    std::env::var("UNWIND_SELL_TOLERANCE_CENTS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(3)
+}
+
+/// Get Kalshi API credentials from environment: the key id
+/// (`KALSHI_API_KEY_ID`) and the path to its PEM private key
+/// (`KALSHI_PRIVATE_KEY_PATH`). `None` unless both are set.
+pub fn kalshi_credentials() -> Option<(String, String)> {
+    let key_id = std::env::var("KALSHI_API_KEY_ID").ok()?;
+    let key_path = std::env::var("KALSHI_PRIVATE_KEY_PATH").ok()?;
+    Some((key_id, key_path))
+}
+
+/// Get the venue environment from `VENUE_ENV` (default: demo).
+/// An unrecognised value is an error rather than a silent fall back to demo.
+pub fn environment() -> anyhow::Result<Environment> {
+    parse_environment(std::env::var("VENUE_ENV").ok().as_deref())
+}
+
+fn parse_environment(value: Option<&str>) -> anyhow::Result<Environment> {
+    match value {
+        None => Ok(Environment::Demo),
+        Some(value) => value
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid VENUE_ENV: {}", e)),
+    }
+}
+
+/// Get venue endpoints for the active environment.
+/// Individual URLs can be overridden with `KALSHI_WS_URL`, `KALSHI_REST_URL`,
+/// `POLY_WS_URL` and `POLY_REST_URL`.
+pub fn endpoints() -> anyhow::Result<Endpoints> {
+    let defaults = Endpoints::for_environment(environment()?);
+    let var = |name: &str, default: String| std::env::var(name).unwrap_or(default);
+    Ok(Endpoints {
+        kalshi_ws: var("KALSHI_WS_URL", defaults.kalshi_ws),
+        kalshi_rest: var("KALSHI_REST_URL", defaults.kalshi_rest),
+        poly_ws: var("POLY_WS_URL", defaults.poly_ws),
+        poly_rest: var("POLY_REST_URL", defaults.poly_rest),
+    })
+}
+
 /// Check if dry run mode is enabled (default: true)
 pub fn is_dry_run() -> bool {
     std::env::var("DRY_RUN")
         .map(|v| v == "1" || v.to_lowercase() == "true")
         .unwrap_or(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_environment_is_rejected() {
+        assert_eq!(parse_environment(None).unwrap(), Environment::Demo);
+        assert_eq!(
+            parse_environment(Some("Production")).unwrap(),
+            Environment::Prod
+        );
+        let err = parse_environment(Some("prodution")).unwrap_err();
+        assert!(err.to_string().contains("prodution"), "{}", err);
+    }
+}
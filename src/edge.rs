@@ -0,0 +1,175 @@
+//! Minimum-edge thresholds and return ranking.
+//!
+//! An edge is measured three ways: profit per contract, return on the capital
+//! tied up, and that return annualized over the time left until settlement.
+//! Opportunities must clear every [`MinEdge`] bound, and are ranked by
+//! annualized return so a 1% edge settling tonight beats 2% settling next
+//! year.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::min_edge;
+use crate::types::{MarketType, Notional};
+
+/// Seconds in a (365-day) year
+const SECS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Shortest holding period used for annualizing. Markets past their
+/// settlement date are still waiting to resolve, and dividing by
+/// (almost) zero would rank them above everything else.
+pub const MIN_HOLDING_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Minimum edge an opportunity must clear on every measure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MinEdge {
+    /// Profit per contract (or basket)
+    #[serde(default)]
+    pub profit: Notional,
+    /// Profit over cost, in basis points
+    #[serde(default)]
+    pub return_bps: i64,
+    /// Return annualized to settlement, in basis points.
+    /// Not applied when the settlement date is unknown.
+    #[serde(default)]
+    pub annualized_bps: i64,
+}
+
+impl MinEdge {
+    /// Whether `edge` clears every bound
+    pub fn accepts(&self, edge: &EdgeReturn) -> bool {
+        edge.profit >= self.profit
+            && edge.return_bps >= self.return_bps
+            && edge.annualized_bps.is_none_or(|a| a >= self.annualized_bps)
+    }
+
+    /// The stricter of each bound, for opportunities spanning several markets
+    pub fn strictest(self, other: MinEdge) -> MinEdge {
+        MinEdge {
+            profit: self.profit.max(other.profit),
+            return_bps: self.return_bps.max(other.return_bps),
+            annualized_bps: self.annualized_bps.max(other.annualized_bps),
+        }
+    }
+}
+
+/// Default minimum edge per market type, read from configuration once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinEdgeDefaults {
+    pub moneyline: MinEdge,
+    pub spread: MinEdge,
+    pub total: MinEdge,
+    pub outcome: MinEdge,
+}
+
+impl MinEdgeDefaults {
+    /// Defaults from configuration
+    pub fn from_config() -> Self {
+        Self {
+            moneyline: min_edge(MarketType::Moneyline),
+            spread: min_edge(MarketType::Spread),
+            total: min_edge(MarketType::Total),
+            outcome: min_edge(MarketType::Outcome),
+        }
+    }
+
+    /// Default for markets of `market_type`
+    pub fn get(&self, market_type: MarketType) -> MinEdge {
+        match market_type {
+            MarketType::Moneyline => self.moneyline,
+            MarketType::Spread => self.spread,
+            MarketType::Total => self.total,
+            MarketType::Outcome => self.outcome,
+        }
+    }
+}
+
+/// Return of one contract (or basket) held to settlement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdgeReturn {
+    /// Payout minus cost
+    pub profit: Notional,
+    /// Profit over cost, in basis points
+    pub return_bps: i64,
+    /// `return_bps` scaled to a year; `None` without a settlement date
+    pub annualized_bps: Option<i64>,
+}
+
+impl EdgeReturn {
+    /// Return on paying `cost` for `profit`, settling in `settles_in`
+    pub fn new(profit: Notional, cost: Notional, settles_in: Option<Duration>) -> Self {
+        let return_bps = if cost.0 > 0 {
+            (profit.0 as i128 * 10_000 / cost.0 as i128) as i64
+        } else {
+            0
+        };
+        let annualized_bps = settles_in.map(|held| {
+            let secs = held.max(MIN_HOLDING_PERIOD).as_secs() as i128;
+            let annualized = return_bps as i128 * SECS_PER_YEAR as i128 / secs;
+            annualized.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+        });
+        Self {
+            profit,
+            return_bps,
+            annualized_bps,
+        }
+    }
+
+    /// Ranking key: annualized return, or the plain return (a one-year hold)
+    /// when the settlement date is unknown
+    pub fn rank(&self) -> i64 {
+        self.annualized_bps.unwrap_or(self.return_bps)
+    }
+}
+
+impl std::fmt::Display for EdgeReturn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.2}%", self.return_bps as f64 / 100.0)?;
+        if let Some(annualized) = self.annualized_bps {
+            write!(f, ", {:.1}%/yr", annualized as f64 / 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Time from `now` until `settles_at`, if known
+pub fn time_to_settlement(
+    settles_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<Duration> {
+    settles_at.map(|at| (at - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annualized_return_ranks_by_time_to_settlement() {
+        let day = Duration::from_secs(24 * 60 * 60);
+        // 1¢ on 99¢ tonight vs 2¢ on 98¢ next year
+        let (one, two) = (Notional::from_cents(1), Notional::from_cents(2));
+        let (cost_99, cost_98) = (Notional::from_cents(99), Notional::from_cents(98));
+        let tonight = EdgeReturn::new(one, cost_99, Some(day));
+        let next_year = EdgeReturn::new(two, cost_98, Some(365 * day));
+        assert_eq!(tonight.return_bps, 101);
+        assert_eq!(tonight.annualized_bps, Some(101 * 365));
+        assert_eq!(next_year.annualized_bps, Some(204));
+        assert!(tonight.rank() > next_year.rank());
+
+        let min = MinEdge {
+            profit: one,
+            return_bps: 150,
+            annualized_bps: 1_000,
+        };
+        assert!(!min.accepts(&tonight)); // Under 1.5%
+        assert!(!min.accepts(&next_year)); // Under 10%/yr
+        let undated = EdgeReturn::new(two, cost_98, None);
+        assert!(min.accepts(&undated));
+
+        // Overdue markets annualize over the minimum holding period
+        let overdue = EdgeReturn::new(one, cost_99, Some(Duration::ZERO));
+        assert_eq!(overdue.annualized_bps, Some(101 * 24 * 365));
+    }
+}
@@ -0,0 +1,427 @@
+//! Live two-leg execution.
+//!
+//! [`ExecutionEngine`] turns an [`ArbOpportunity`] into immediate-or-cancel
+//! orders. The riskier leg (the thinner book, or the slower venue) goes
+//! first; the second leg is sized to what the first actually filled, so a
+//! partial or rejected first leg never leaves the second one naked. Whatever
+//! the second leg fails to cover is reported as unhedged.
+
+use anyhow::Result;
+use futures_util::future::BoxFuture;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::config::PAIR_PAYOUT;
+use crate::orders::{Order, OrderAction, OrderEvent, OrderHook, OrderManager, TimeInForce};
+use crate::types::{ArbOpportunity, Contracts, Leg, Notional, Side, Venue};
+
+/// Order entry on one venue
+pub trait OrderApi: Send + Sync {
+    fn venue(&self) -> Venue;
+
+    /// Typical time from sending an order to its fill report
+    fn latency(&self) -> Duration;
+
+    /// Send `order` and return what the venue reported: its ack or
+    /// rejection, fills, and for an immediate-or-cancel order the cancel of
+    /// whatever did not fill. An error means the outcome is unknown.
+    fn place<'a>(&'a self, order: &'a Order) -> BoxFuture<'a, Result<Vec<OrderEvent>>>;
+
+    /// Cancel what is left of `order` and return what the venue reported,
+    /// including any fills it had not reported yet
+    fn cancel<'a>(&'a self, order: &'a Order) -> BoxFuture<'a, Result<Vec<OrderEvent>>>;
+}
+
+/// Share of an order's total cost paid for `quantity` of its fills
+pub(crate) fn cost_of(order: &Order, quantity: Contracts) -> Notional {
+    let filled = order.filled.get().max(1) as i128;
+    Notional((order.total_cost().0 as i128 * quantity.get() as i128 / filled) as i64)
+}
+
+/// Outcome of executing one opportunity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionReport {
+    pub market_id: String,
+    pub first: Order,
+    /// Not sent when the first leg filled nothing
+    pub second: Option<Order>,
+    /// Contracts held on both legs
+    pub hedged: Contracts,
+}
+
+impl ExecutionReport {
+    /// Everything paid on both legs, fees included
+    pub fn realized_cost(&self) -> Notional {
+        let second = self
+            .second
+            .as_ref()
+            .map_or(Notional::ZERO, Order::total_cost);
+        self.first.total_cost().saturating_add(second)
+    }
+
+    /// First-leg contracts the second leg did not cover
+    pub fn unhedged(&self) -> Option<(&Leg, Contracts)> {
+        let naked = self.first.filled.saturating_sub(self.hedged);
+        (!naked.is_zero()).then_some((&self.first.leg, naked))
+    }
+
+    /// Payout of the hedged pairs minus what they cost
+    pub fn locked_pnl(&self) -> Notional {
+        let Some(second) = &self.second else {
+            return Notional::ZERO;
+        };
+        let payout = self.hedged.notional(PAIR_PAYOUT).unwrap_or(Notional::ZERO);
+        payout
+            .saturating_sub(cost_of(&self.first, self.hedged))
+            .saturating_sub(cost_of(second, self.hedged))
+    }
+}
+
+/// Which outcome's leg to send first: the thinner book, or on a tie the
+/// slower venue. The first leg is the one most likely to come back short,
+/// and the second is only sized once it has.
+pub fn first_leg(arb: &ArbOpportunity, yes_latency: Duration, no_latency: Duration) -> Side {
+    match arb.yes_size.cmp(&arb.no_size) {
+        std::cmp::Ordering::Less => Side::Yes,
+        std::cmp::Ordering::Greater => Side::No,
+        std::cmp::Ordering::Equal if no_latency > yes_latency => Side::No,
+        std::cmp::Ordering::Equal => Side::Yes,
+    }
+}
+
+/// Orders the engine is waiting on. A fill on any other order arrived
+/// after the engine gave up on it and hedged (or didn't) without it.
+#[derive(Debug, Default)]
+struct AwaitedOrders(Mutex<HashSet<String>>);
+
+impl OrderHook for AwaitedOrders {
+    fn on_order_event(&self, order: &Order, event: &OrderEvent) {
+        if let OrderEvent::Fill { quantity, .. } = event {
+            if !self.0.lock().unwrap().contains(&order.client_order_id) {
+                warn!(
+                    "[EXECUTION] ⚠️  Late fill of {} on {} ({}); not hedged",
+                    quantity, order.leg, order.client_order_id
+                );
+            }
+        }
+    }
+}
+
+/// Sends both legs of an arb to the venues' order APIs
+pub struct ExecutionEngine {
+    kalshi: Arc<dyn OrderApi>,
+    poly: Arc<dyn OrderApi>,
+    orders: Arc<OrderManager>,
+    awaited: Arc<AwaitedOrders>,
+    /// How long to wait for an order's fills before giving up on it
+    order_timeout: Duration,
+}
+
+impl ExecutionEngine {
+    /// Engine placing orders through `orders`, which it subscribes to.
+    /// Subscribe the position tracker to `orders` too, so fills update
+    /// positions as they arrive.
+    pub fn new(
+        kalshi: Arc<dyn OrderApi>,
+        poly: Arc<dyn OrderApi>,
+        orders: Arc<OrderManager>,
+        order_timeout: Duration,
+    ) -> Self {
+        let awaited = Arc::new(AwaitedOrders::default());
+        orders.subscribe(awaited.clone());
+        Self {
+            kalshi,
+            poly,
+            orders,
+            awaited,
+            order_timeout,
+        }
+    }
+
+    pub(crate) fn orders(&self) -> &OrderManager {
+        &self.orders
+    }
+
+    fn api(&self, venue: Venue) -> &dyn OrderApi {
+        match venue {
+            Venue::Kalshi => self.kalshi.as_ref(),
+            Venue::Polymarket => self.poly.as_ref(),
+        }
+    }
+
+    /// Place one immediate-or-cancel order and apply the venue's answer.
+    ///
+    /// An order with no answer in time stays open (`Sent`) in the order
+    /// manager and counts as unfilled here.
+    pub(crate) async fn send(&self, order: Order) -> Order {
+        let order = self.apply(order, OrderEvent::Sent);
+        self.request(order, false).await
+    }
+
+    /// Cancel what is left of an open order, collecting any fills the venue
+    /// had not reported
+    pub(crate) async fn cancel(&self, order: Order) -> Order {
+        self.request(order, true).await
+    }
+
+    /// Place (or cancel) `order` and apply the events the venue returns
+    async fn request(&self, mut order: Order, cancel: bool) -> Order {
+        let id = order.client_order_id.clone();
+        self.awaited.0.lock().unwrap().insert(id.clone());
+        let api = self.api(order.venue());
+        let request = if cancel {
+            api.cancel(&order)
+        } else {
+            api.place(&order)
+        };
+        match tokio::time::timeout(self.order_timeout, request).await {
+            Ok(Ok(events)) => {
+                for event in events {
+                    order = self.apply(order, event);
+                }
+            }
+            Ok(Err(e)) => warn!("[EXECUTION] {} on {}: {}", id, order.leg, e),
+            Err(_) => warn!(
+                "[EXECUTION] {} on {}: no response after {}ms",
+                id,
+                order.leg,
+                self.order_timeout.as_millis()
+            ),
+        }
+
+        self.awaited.0.lock().unwrap().remove(&id);
+        order
+    }
+
+    /// Apply one event, keeping the order as it was if the event is invalid
+    fn apply(&self, order: Order, event: OrderEvent) -> Order {
+        match self.orders.apply(&order.client_order_id, event) {
+            Ok(updated) => updated,
+            Err(e) => {
+                warn!("[EXECUTION] Ignoring order event: {}", e);
+                order
+            }
+        }
+    }
+
+    /// Buy up to `quantity` pairs of `arb`, first leg first
+    pub async fn execute(&self, arb: &ArbOpportunity, quantity: Contracts) -> ExecutionReport {
+        let latency = |leg: &Leg| self.api(leg.venue).latency();
+        let first_side = first_leg(arb, latency(&arb.yes_leg), latency(&arb.no_leg));
+        let order = |side: Side, quantity: Contracts| {
+            let (leg, limit) = match side {
+                Side::Yes => (arb.yes_leg.clone(), arb.yes_limit.floor_to(arb.yes_tick)),
+                Side::No => (arb.no_leg.clone(), arb.no_limit.floor_to(arb.no_tick)),
+            };
+            let tif = TimeInForce::ImmediateOrCancel;
+            let action = OrderAction::Buy;
+            self.orders
+                .create(&arb.market_id, leg, action, limit, quantity, tif)
+        };
+
+        let first = self.send(order(first_side, quantity)).await;
+        if first.filled.is_zero() {
+            return ExecutionReport {
+                market_id: arb.market_id.clone(),
+                first,
+                second: None,
+                hedged: Contracts::ZERO,
+            };
+        }
+
+        let second = self.send(order(first_side.opposite(), first.filled)).await;
+        ExecutionReport {
+            market_id: arb.market_id.clone(),
+            hedged: first.filled.min(second.filled),
+            first,
+            second: Some(second),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::edge::EdgeReturn;
+    use crate::fees::PairFees;
+    use crate::orders::OrderState;
+    use crate::position_tracker::PositionTracker;
+    use crate::types::{ArbType, Price};
+
+    /// Venue that fills up to `available` contracts at the order's limit
+    /// and cancels the rest
+    pub(crate) struct MockVenue {
+        pub venue: Venue,
+        pub latency: Duration,
+        pub available: Mutex<Contracts>,
+        pub reject: Option<&'static str>,
+        pub orders: Mutex<Vec<Order>>,
+    }
+
+    impl MockVenue {
+        pub fn new(venue: Venue, available: u64) -> Self {
+            Self {
+                venue,
+                latency: Duration::from_millis(50),
+                available: Mutex::new(Contracts(available)),
+                reject: None,
+                orders: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl OrderApi for MockVenue {
+        fn venue(&self) -> Venue {
+            self.venue
+        }
+
+        fn latency(&self) -> Duration {
+            self.latency
+        }
+
+        fn place<'a>(&'a self, order: &'a Order) -> BoxFuture<'a, Result<Vec<OrderEvent>>> {
+            Box::pin(async move {
+                let mut orders = self.orders.lock().unwrap();
+                orders.push(order.clone());
+                if let Some(reason) = self.reject {
+                    let reason = reason.to_string();
+                    return Ok(vec![OrderEvent::Rejected { reason }]);
+                }
+                let venue_order_id = format!("{}-{}", self.venue.tag(), orders.len());
+                let mut events = vec![OrderEvent::Acked { venue_order_id }];
+                let mut available = self.available.lock().unwrap();
+                let filled = order.quantity.min(*available);
+                *available = available.saturating_sub(filled);
+                if !filled.is_zero() {
+                    events.push(OrderEvent::Fill {
+                        quantity: filled,
+                        price: order.price,
+                        fee: Notional::ZERO,
+                    });
+                }
+                if filled < order.quantity {
+                    events.push(OrderEvent::Cancelled);
+                }
+                Ok(events)
+            })
+        }
+
+        fn cancel<'a>(&'a self, order: &'a Order) -> BoxFuture<'a, Result<Vec<OrderEvent>>> {
+            let open = order.state.is_open();
+            Box::pin(async move {
+                Ok(if open {
+                    vec![OrderEvent::Cancelled]
+                } else {
+                    vec![]
+                })
+            })
+        }
+    }
+
+    /// Kalshi YES 40¢ + Polymarket NO 55¢, `yes_size` on YES and 100 on NO
+    pub(crate) fn arb(yes_size: u64) -> ArbOpportunity {
+        let pair = crate::config::get_hardcoded_markets().remove(0);
+        let (yes_price, no_price) = (Price::from_cents(40), Price::from_cents(55));
+        ArbOpportunity {
+            market_id: pair.id.clone(),
+            description: pair.description.clone(),
+            arb_type: ArbType::KalshiYesPolyNo,
+            yes_price,
+            no_price,
+            total_cost: yes_price + no_price,
+            fee: Price::ZERO,
+            profit: Price::from_cents(5),
+            quantity: Contracts(yes_size.min(100)),
+            yes_vwap: 40.0,
+            no_vwap: 55.0,
+            yes_limit: yes_price,
+            no_limit: no_price,
+            yes_tick: Price::CENT,
+            no_tick: Price::CENT,
+            yes_size: Contracts(yes_size),
+            no_size: Contracts(100),
+            expected_profit: Notional::ZERO,
+            edge_return: EdgeReturn::new(Notional::from_cents(5), Notional::from_cents(95), None),
+            fees: PairFees::default(),
+            yes_leg: pair.leg(Venue::Kalshi, Side::Yes),
+            no_leg: pair.leg(Venue::Polymarket, Side::No),
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    pub(crate) fn engine(kalshi: &Arc<MockVenue>, poly: &Arc<MockVenue>) -> ExecutionEngine {
+        let orders = Arc::new(OrderManager::new());
+        ExecutionEngine::new(kalshi.clone(), poly.clone(), orders, Duration::from_secs(1))
+    }
+
+    #[tokio::test]
+    async fn test_second_leg_sized_to_first_fill() {
+        let kalshi = Arc::new(MockVenue::new(Venue::Kalshi, 6));
+        let poly = Arc::new(MockVenue::new(Venue::Polymarket, 100));
+        let engine = engine(&kalshi, &poly);
+
+        // The thin Kalshi YES book goes first and only fills 6 of 10
+        let report = engine.execute(&arb(10), Contracts(10)).await;
+        assert_eq!(report.first.leg.venue, Venue::Kalshi);
+        assert_eq!(report.first.state, OrderState::Cancelled);
+        assert_eq!(poly.orders.lock().unwrap()[0].quantity, Contracts(6));
+        assert_eq!(report.second.as_ref().unwrap().state, OrderState::Filled);
+        assert_eq!(report.hedged, Contracts(6));
+        assert!(report.unhedged().is_none());
+        assert_eq!(report.realized_cost(), Notional::from_cents(6 * 95));
+        assert_eq!(report.locked_pnl(), Notional::from_cents(6 * 5));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_second_leg_is_reported_unhedged() {
+        let kalshi = Arc::new(MockVenue::new(Venue::Kalshi, 100));
+        let poly = Arc::new(MockVenue {
+            reject: Some("market closed"),
+            ..MockVenue::new(Venue::Polymarket, 100)
+        });
+        let engine = engine(&kalshi, &poly);
+
+        let report = engine.execute(&arb(10), Contracts(10)).await;
+        let second = report.second.as_ref().unwrap();
+        assert_eq!(second.state, OrderState::Rejected);
+        assert_eq!(second.reject_reason.as_deref(), Some("market closed"));
+        assert_eq!(report.hedged, Contracts::ZERO);
+        let (leg, naked) = report.unhedged().unwrap();
+        assert_eq!((leg.venue, naked), (Venue::Kalshi, Contracts(10)));
+        assert_eq!(report.realized_cost(), Notional::from_cents(400));
+
+        // Nothing on the first leg: the second is never sent
+        *kalshi.available.lock().unwrap() = Contracts::ZERO;
+        let report = engine.execute(&arb(10), Contracts(10)).await;
+        assert!(report.second.is_none());
+        assert_eq!(poly.orders.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_late_fill_after_cancel_reaches_positions() {
+        let kalshi = Arc::new(MockVenue::new(Venue::Kalshi, 6));
+        let poly = Arc::new(MockVenue::new(Venue::Polymarket, 100));
+        let engine = engine(&kalshi, &poly);
+        let tracker = Arc::new(std::sync::RwLock::new(PositionTracker::new()));
+        engine.orders().subscribe(tracker.clone());
+
+        let report = engine.execute(&arb(10), Contracts(10)).await;
+        assert_eq!(report.first.state, OrderState::Cancelled);
+
+        // Kalshi reports two more contracts after the IOC's cancel
+        let late = OrderEvent::Fill {
+            quantity: Contracts(2),
+            price: Price::from_cents(40),
+            fee: Notional::ZERO,
+        };
+        let first = engine
+            .orders()
+            .apply(&report.first.client_order_id, late)
+            .unwrap();
+        assert_eq!(first.filled, Contracts(8));
+        let exposure = tracker.read().unwrap().exposure(&report.market_id);
+        assert_eq!(exposure.unhedged(), Contracts(2));
+    }
+}
@@ -1,101 +1,594 @@
 //! Arbitrage detection and execution logic.
 //!
-//! MVP version: detects arbitrage opportunities and logs them (dry-run only).
-//! Does NOT execute actual trades.
+//! Detects arbitrage opportunities and hands them to execution: logged and
+//! tracked as hypothetical positions in dry-run mode, or sent to the venues
+//! through the [`ExecutionEngine`] in live mode.
 
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
-use crate::config::{is_dry_run, max_position_size, ARB_THRESHOLD_CENTS};
+use crate::basket::detect_basket;
+use crate::config::{
+    is_dry_run, max_leg_skew_ms, max_position_size, max_quote_age_ms, DETECTION_SWEEP_INTERVAL_MS,
+    PAIR_PAYOUT,
+};
+use crate::edge::{EdgeReturn, MinEdgeDefaults};
+use crate::engine::ExecutionEngine;
+use crate::feed::BookUpdate;
+use crate::fees::FeeModel;
+use crate::lifecycle::{OpportunityEvent, OpportunityKey, OpportunityLifecycle, Transition};
+use crate::lines::detect_middle;
 use crate::position_tracker::PositionTracker;
+use crate::registry::MarketRegistry;
+use crate::supervisor::{ConnectionHealth, FeedLiveness};
 use crate::types::{
-    kalshi_fee_cents, ArbOpportunity, ArbType, MarketState, NO_PRICE,
+    ArbOpportunity, ArbType, BasketOpportunity, BookStamp, Contracts, EventGroup, Leg, MarketState,
+    MiddleOpportunity, Notional, Opportunity, Price, PriceLevel, Side, Venue, NO_PRICE,
 };
+use crate::unwind::{unwind, UnwindPolicy};
+
+/// Quote freshness limits applied to every leg of an opportunity
+#[derive(Debug, Clone, Copy)]
+pub struct FreshnessLimits {
+    /// Maximum time since a leg was last updated
+    pub max_age: Duration,
+    /// Maximum time between the two legs' updates
+    pub max_skew: Duration,
+    /// Feed liveness legs are confirmed against
+    pub feeds: FeedLiveness,
+}
+
+impl FreshnessLimits {
+    /// Limits from configuration
+    pub fn from_config() -> Self {
+        Self {
+            max_age: Duration::from_millis(max_quote_age_ms()),
+            max_skew: Duration::from_millis(max_leg_skew_ms()),
+            feeds: FeedLiveness::default(),
+        }
+    }
+
+    /// These limits, with legs confirmed by the feeds' current liveness
+    pub fn with_feeds(self, health: &ConnectionHealth) -> Self {
+        Self {
+            feeds: health.liveness(),
+            ..self
+        }
+    }
+
+    /// A `venue` leg's stamp, aged from the last frame on its connection
+    /// rather than from the book's last change
+    pub fn confirm(&self, venue: Venue, stamp: Option<BookStamp>) -> Option<BookStamp> {
+        stamp.map(|stamp| stamp.confirmed(self.feeds.last_frame(venue)))
+    }
+}
+
+/// Detection counters and latency
+#[derive(Debug, Default)]
+pub struct DetectionStats {
+    /// Skipped because a leg was older than the max quote age
+    pub stale_legs: AtomicU64,
+    /// Skipped because the legs were updated too far apart
+    pub skewed_legs: AtomicU64,
+    /// Opportunities emitted in response to a book update
+    pub triggered: AtomicU64,
+    /// Opportunities that appeared
+    pub opened: AtomicU64,
+    /// Opportunities that went away
+    pub closed: AtomicU64,
+    /// Sum of receipt-to-emission latency over `triggered`, in microseconds
+    latency_total_us: AtomicU64,
+    /// Worst receipt-to-emission latency, in microseconds
+    latency_max_us: AtomicU64,
+}
+
+impl DetectionStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the time from frame receipt to opportunity emission
+    pub fn record_latency(&self, latency: Duration) {
+        let micros = latency.as_micros() as u64;
+        self.triggered.fetch_add(1, Ordering::Relaxed);
+        self.latency_total_us.fetch_add(micros, Ordering::Relaxed);
+        self.latency_max_us.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    /// Mean receipt-to-emission latency, if anything was emitted
+    pub fn mean_latency(&self) -> Option<Duration> {
+        let count = self.triggered.load(Ordering::Relaxed);
+        (count > 0)
+            .then(|| Duration::from_micros(self.latency_total_us.load(Ordering::Relaxed) / count))
+    }
+
+    /// Worst receipt-to-emission latency seen
+    pub fn max_latency(&self) -> Duration {
+        Duration::from_micros(self.latency_max_us.load(Ordering::Relaxed))
+    }
+
+    /// One-line summary for the heartbeat log
+    pub fn summary(&self) -> String {
+        let latency = match self.mean_latency() {
+            Some(mean) => format!(
+                "avg {}µs, max {}µs over {}",
+                mean.as_micros(),
+                self.max_latency().as_micros(),
+                self.triggered.load(Ordering::Relaxed)
+            ),
+            None => "n/a".to_string(),
+        };
+        let opened = self.opened.load(Ordering::Relaxed);
+        let closed = self.closed.load(Ordering::Relaxed);
+        format!(
+            "Opportunities: {} opened, {} open | Skipped arbs: {} stale, {} skewed | Detection latency: {}",
+            opened,
+            opened.saturating_sub(closed),
+            self.stale_legs.load(Ordering::Relaxed),
+            self.skewed_legs.load(Ordering::Relaxed),
+            latency
+        )
+    }
+}
 
-/// Check all markets for arbitrage opportunities
+/// Check markets for arbitrage opportunities as their books change.
+///
+/// Each book update re-checks only the touched market and the events it is
+/// an outcome of. A full sweep of every market and event runs every
+/// `DETECTION_SWEEP_INTERVAL_MS` as a safety net.
 pub async fn check_arbitrage_opportunities(
-    markets: Arc<RwLock<HashMap<String, Arc<MarketState>>>>,
+    registry: Arc<MarketRegistry>,
     position_tracker: Arc<RwLock<PositionTracker>>,
-    arb_tx: mpsc::UnboundedSender<ArbOpportunity>,
+    arb_tx: mpsc::UnboundedSender<Opportunity>,
+    stats: Arc<DetectionStats>,
+    health: Arc<ConnectionHealth>,
+    mut updates: mpsc::UnboundedReceiver<BookUpdate>,
+    events: mpsc::UnboundedSender<OpportunityEvent>,
 ) {
-    let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(500));
-
-    loop {
-        interval.tick().await;
+    let mut sweep = tokio::time::interval(Duration::from_millis(DETECTION_SWEEP_INTERVAL_MS));
+    let config_limits = FreshnessLimits::from_config();
+    let min_edges = MinEdgeDefaults::from_config();
+    let mut lifecycle = OpportunityLifecycle::new();
+    let detect = |market: &MarketState| -> Detected {
+        let limits = config_limits.with_feeds(&health);
+        let found =
+            detect_arbitrage(market, &limits, &min_edges, &stats).and_then(|arb| match arb {
+                Some(arb) => Ok(vec![Opportunity::Pair(arb)]),
+                None => detect_middle(market, &limits, &stats)
+                    .map(|middles| middles.into_iter().map(Opportunity::Middle).collect()),
+            });
+        match found {
+            Ok(found) => Detected {
+                found,
+                unjudged: false,
+            },
+            Err(_) => Detected {
+                found: Vec::new(),
+                unjudged: true,
+            },
+        }
+    };
+    let detect_baskets = |group: &EventGroup| -> Detected {
+        let mut detected = Detected {
+            found: Vec::new(),
+            unjudged: false,
+        };
+        let Some(markets) = registry.event_markets(group) else {
+            return detected;
+        };
+        let limits = config_limits.with_feeds(&health);
+        for side in [Side::Yes, Side::No] {
+            match detect_basket(group, &markets, side, &limits, &min_edges, &stats) {
+                Ok(basket) => detected.found.extend(basket.map(Opportunity::Basket)),
+                Err(_) => detected.unjudged = true,
+            }
+        }
+        detected
+    };
+    // Hand new or improved opportunities in `scope` to execution and close
+    // the ones that are gone. A pass that could not judge every leg closes
+    // nothing: its missing opportunities may still be there.
+    let mut publish = |scope: &str, detected: Detected| -> bool {
+        let now = Instant::now();
+        let found = detected.found;
+        let keys: Vec<OpportunityKey> = found.iter().map(Opportunity::key).collect();
+        let closed = if detected.unjudged {
+            Vec::new()
+        } else {
+            lifecycle.close_missing(scope, &keys)
+        };
+        for (key, opportunity) in closed {
+            stats.closed.fetch_add(1, Ordering::Relaxed);
+            let _ = events.send(OpportunityEvent::Closed { key, opportunity });
+        }
 
-        let markets_guard = markets.read().unwrap();
-        for market in markets_guard.values() {
-            if let Some(arb) = detect_arbitrage(market) {
-                // Check position limits
+        let mut sent = false;
+        for (opportunity, key) in found.into_iter().zip(keys) {
+            let transition = lifecycle.observe(&opportunity, now);
+            if transition == Transition::Opened {
+                stats.opened.fetch_add(1, Ordering::Relaxed);
+            }
+            if let Some(event) = OpportunityEvent::of(&key, transition, opportunity.edge()) {
+                let _ = events.send(event);
+            }
+            if !lifecycle.should_hand(&key) {
+                continue;
+            }
+            // Middles are reported only; position limits don't apply
+            if !matches!(opportunity, Opportunity::Middle(_)) {
                 let tracker = position_tracker.read().unwrap();
-                if tracker.can_trade(&market.pair.id, max_position_size()) {
-                    drop(tracker);
-                    let _ = arb_tx.send(arb);
-                } else {
-                    warn!(
-                        "[ARB] Position limit reached for {}",
-                        market.pair.description
-                    );
+                if !tracker.can_trade(&key.scope, max_position_size()) {
+                    // Held back, not handed: retried while it stands
+                    if transition != Transition::Unchanged {
+                        warn!("[ARB] Position limit reached for {}", key.scope);
+                    }
+                    continue;
+                }
+            }
+            if arb_tx.send(opportunity).is_ok() {
+                lifecycle.mark_handed(&key);
+                sent = true;
+            }
+        }
+        sent
+    };
+
+    loop {
+        tokio::select! {
+            Some(update) = updates.recv() => {
+                // Drain the backlog so a burst checks each market once, timed
+                // from its oldest pending update
+                let mut batch = vec![update];
+                while let Ok(update) = updates.try_recv() {
+                    batch.push(update);
+                }
+                let mut checked = HashSet::new();
+                let mut checked_events = HashSet::new();
+                // (scope, opportunities, index of the update that triggered it)
+                let mut scopes = Vec::new();
+                for (i, update) in batch.iter().enumerate() {
+                    let market = &update.market;
+                    if !checked.insert(market.pair.id.clone()) {
+                        continue;
+                    }
+                    scopes.push((market.pair.id.clone(), detect(market), i));
+                    for group in registry.events_for(&market.pair.id) {
+                        if checked_events.insert(group.id.clone()) {
+                            scopes.push((group.id.clone(), detect_baskets(&group), i));
+                        }
+                    }
+                }
+                let mut emitted = HashSet::new();
+                for (scope, detected, i) in ranked(scopes) {
+                    if publish(&scope, detected) {
+                        emitted.insert(i);
+                    }
+                }
+                for i in emitted {
+                    stats.record_latency(batch[i].received_at.elapsed());
+                }
+            }
+            _ = sweep.tick() => {
+                let markets = registry
+                    .all()
+                    .into_iter()
+                    .map(|market| (market.pair.id.clone(), detect(&market), ()));
+                let events = registry
+                    .events()
+                    .into_iter()
+                    .map(|group| (group.id.clone(), detect_baskets(&group), ()));
+                for (scope, detected, ()) in ranked(markets.chain(events).collect()) {
+                    publish(&scope, detected);
                 }
             }
         }
-        drop(markets_guard);
     }
 }
 
-/// Detect arbitrage opportunity for a single market
-fn detect_arbitrage(market: &MarketState) -> Option<ArbOpportunity> {
-    let kalshi = market.kalshi.read().unwrap();
-    let poly = market.poly.read().unwrap();
+/// What one detection pass found in a scope
+struct Detected {
+    found: Vec<Opportunity>,
+    /// Stale or skewed legs were skipped, so an opportunity missing from
+    /// `found` may not actually be gone
+    unjudged: bool,
+}
 
-    let k_yes = kalshi.yes_ask;
-    let k_no = kalshi.no_ask;
-    let p_yes = poly.yes_ask;
-    let p_no = poly.no_ask;
+/// Order detection results best-first by their top opportunity's
+/// [`Opportunity::rank`], so execution sees the best return first.
+/// Scopes with nothing found keep their place at the end (they only close).
+fn ranked<T>(mut scopes: Vec<(String, Detected, T)>) -> Vec<(String, Detected, T)> {
+    scopes.sort_by_key(|(_, detected, _)| {
+        std::cmp::Reverse(detected.found.iter().map(Opportunity::rank).max())
+    });
+    scopes
+}
+
+/// Why a leg pair may not be traded on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Staleness {
+    /// A leg has no update time or is older than the max age
+    Stale,
+    /// The legs were updated too far apart
+    Skewed,
+}
+
+/// Check two legs against the freshness limits
+pub(crate) fn check_freshness(
+    yes: Option<BookStamp>,
+    no: Option<BookStamp>,
+    limits: &FreshnessLimits,
+    now: Instant,
+) -> Result<(), Staleness> {
+    let (yes, no) = match (yes, no) {
+        (Some(yes), Some(no)) => (yes, no),
+        _ => return Err(Staleness::Stale),
+    };
+    if yes.age(now) > limits.max_age || no.age(now) > limits.max_age {
+        return Err(Staleness::Stale);
+    }
+    if yes.skew(&no) > limits.max_skew {
+        return Err(Staleness::Skewed);
+    }
+    Ok(())
+}
+
+/// One YES + NO leg combination to check
+struct Combo<'a> {
+    arb_type: ArbType,
+    yes_asks: &'a [PriceLevel],
+    no_asks: &'a [PriceLevel],
+    yes_fee: &'a dyn FeeModel,
+    no_fee: &'a dyn FeeModel,
+    yes_tick: Price,
+    no_tick: Price,
+    yes_stamp: Option<BookStamp>,
+    no_stamp: Option<BookStamp>,
+}
+
+/// Executable depth of a YES + NO pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DepthFill {
+    /// Contracts bought on each leg
+    pub quantity: Contracts,
+    /// Total paid for the YES leg
+    pub yes_cost: Notional,
+    /// Total paid for the NO leg
+    pub no_cost: Notional,
+    /// Total fees on both legs
+    pub fees: Notional,
+    /// Worst YES price taken
+    pub yes_limit: Price,
+    /// Worst NO price taken
+    pub no_limit: Price,
+}
+
+impl DepthFill {
+    /// Volume-weighted YES price, in cents
+    pub fn yes_vwap(&self) -> f64 {
+        self.yes_cost.cents() / self.quantity.get().max(1) as f64
+    }
+
+    /// Volume-weighted NO price, in cents
+    pub fn no_vwap(&self) -> f64 {
+        self.no_cost.cents() / self.quantity.get().max(1) as f64
+    }
+
+    /// Payout minus everything paid
+    pub fn profit(&self) -> Notional {
+        let payout = self
+            .quantity
+            .notional(PAIR_PAYOUT)
+            .unwrap_or(Notional(i64::MAX));
+        payout
+            .saturating_sub(self.yes_cost)
+            .saturating_sub(self.no_cost)
+            .saturating_sub(self.fees)
+    }
+
+    /// Add `quantity` pairs at the given prices; `None` on overflow
+    fn take(&self, quantity: Contracts, yes: Price, no: Price) -> Option<Self> {
+        Some(Self {
+            quantity: self.quantity.checked_add(quantity)?,
+            yes_cost: self.yes_cost.checked_add(quantity.notional(yes)?)?,
+            no_cost: self.no_cost.checked_add(quantity.notional(no)?)?,
+            fees: self.fees,
+            yes_limit: self.yes_limit.max(yes),
+            no_limit: self.no_limit.max(no),
+        })
+    }
+
+    /// Charge the fee for `taken` contracts filled at one `level`
+    fn charge(&mut self, fee: &dyn FeeModel, level: &PriceLevel, taken: Contracts) {
+        if !taken.is_zero() {
+            self.fees = self.fees.saturating_add(fee.order_fee(level.price, taken));
+        }
+    }
+}
+
+/// Walk both ask ladders best-first, pairing YES and NO contracts for as long
+/// as the marginal pair (both prices plus fees) costs less than the payout.
+///
+/// Each level taken is charged as one order, so per-order fee rounding can
+/// leave a thin fill with no profit; callers check [`DepthFill::profit`].
+pub fn walk_depth(
+    yes: &[PriceLevel],
+    no: &[PriceLevel],
+    yes_fee: &dyn FeeModel,
+    no_fee: &dyn FeeModel,
+) -> DepthFill {
+    let mut fill = DepthFill::default();
+    let (mut yes_levels, mut no_levels) = (yes.iter(), no.iter());
+    let (mut yes_level, mut no_level) = (yes_levels.next().copied(), no_levels.next().copied());
+    // Contracts taken so far from the current level of each ladder
+    let (mut yes_taken, mut no_taken) = (Contracts::ZERO, Contracts::ZERO);
+
+    while let (Some(y), Some(n)) = (yes_level.as_mut(), no_level.as_mut()) {
+        let fees = yes_fee.unit_fee(y.price) + no_fee.unit_fee(n.price);
+        if y.price + n.price + fees >= PAIR_PAYOUT {
+            break;
+        }
+
+        let quantity = y.size.min(n.size);
+        match fill.take(quantity, y.price, n.price) {
+            Some(next) => fill = next,
+            None => break, // Deeper than any notional we can represent
+        }
+        yes_taken = yes_taken.saturating_add(quantity);
+        no_taken = no_taken.saturating_add(quantity);
+
+        y.size = y.size.saturating_sub(quantity);
+        n.size = n.size.saturating_sub(quantity);
+        if y.size.is_zero() {
+            fill.charge(yes_fee, y, yes_taken);
+            yes_taken = Contracts::ZERO;
+            yes_level = yes_levels.next().copied();
+        }
+        if n.size.is_zero() {
+            fill.charge(no_fee, n, no_taken);
+            no_taken = Contracts::ZERO;
+            no_level = no_levels.next().copied();
+        }
+    }
+
+    // Levels the walk stopped part-way through
+    if let Some(y) = &yes_level {
+        fill.charge(yes_fee, y, yes_taken);
+    }
+    if let Some(n) = &no_level {
+        fill.charge(no_fee, n, no_taken);
+    }
+    fill
+}
+
+/// Detect arbitrage opportunity for a single market.
+/// `Err` if stale or skewed legs left nothing to report.
+fn detect_arbitrage(
+    market: &MarketState,
+    limits: &FreshnessLimits,
+    min_edges: &MinEdgeDefaults,
+    stats: &DetectionStats,
+) -> Result<Option<ArbOpportunity>, Staleness> {
+    let kalshi = market.kalshi.load();
+    let poly = market.poly.load();
+    let fees = &market.pair.fees;
+    let min_edge = market.pair.edge_threshold(min_edges);
+    let settles_in = market.pair.settles_in(chrono::Utc::now());
+    let now = Instant::now();
 
     // Skip if any price is missing
-    if k_yes == NO_PRICE || k_no == NO_PRICE || p_yes == NO_PRICE || p_no == NO_PRICE {
-        return None;
+    if kalshi.yes_ask == NO_PRICE
+        || kalshi.no_ask == NO_PRICE
+        || poly.yes_ask == NO_PRICE
+        || poly.no_ask == NO_PRICE
+    {
+        return Ok(None);
     }
 
     // Check all 4 possible arbitrage combinations
-    let opportunities = vec![
+    let opportunities = [
         // Cross-platform: Poly YES + Kalshi NO
-        (
-            ArbType::PolyYesKalshiNo,
-            p_yes,
-            k_no,
-            kalshi_fee_cents(k_no),
-        ),
+        Combo {
+            arb_type: ArbType::PolyYesKalshiNo,
+            yes_asks: poly.asks(Side::Yes),
+            no_asks: kalshi.asks(Side::No),
+            yes_fee: &fees.poly,
+            no_fee: &fees.kalshi,
+            yes_tick: poly.tick(),
+            no_tick: kalshi.tick(),
+            yes_stamp: limits.confirm(Venue::Polymarket, poly.yes_stamp),
+            no_stamp: limits.confirm(Venue::Kalshi, kalshi.no_stamp),
+        },
         // Cross-platform: Kalshi YES + Poly NO
-        (
-            ArbType::KalshiYesPolyNo,
-            k_yes,
-            p_no,
-            kalshi_fee_cents(k_yes),
-        ),
-        // Same-platform: Poly YES + Poly NO (no fees)
-        (ArbType::PolyOnly, p_yes, p_no, 0),
+        Combo {
+            arb_type: ArbType::KalshiYesPolyNo,
+            yes_asks: kalshi.asks(Side::Yes),
+            no_asks: poly.asks(Side::No),
+            yes_fee: &fees.kalshi,
+            no_fee: &fees.poly,
+            yes_tick: kalshi.tick(),
+            no_tick: poly.tick(),
+            yes_stamp: limits.confirm(Venue::Kalshi, kalshi.yes_stamp),
+            no_stamp: limits.confirm(Venue::Polymarket, poly.no_stamp),
+        },
+        // Same-platform: Poly YES + Poly NO
+        Combo {
+            arb_type: ArbType::PolyOnly,
+            yes_asks: poly.asks(Side::Yes),
+            no_asks: poly.asks(Side::No),
+            yes_fee: &fees.poly,
+            no_fee: &fees.poly,
+            yes_tick: poly.tick(),
+            no_tick: poly.tick(),
+            yes_stamp: limits.confirm(Venue::Polymarket, poly.yes_stamp),
+            no_stamp: limits.confirm(Venue::Polymarket, poly.no_stamp),
+        },
         // Same-platform: Kalshi YES + Kalshi NO (double fees)
-        (
-            ArbType::KalshiOnly,
-            k_yes,
-            k_no,
-            kalshi_fee_cents(k_yes) + kalshi_fee_cents(k_no),
-        ),
+        Combo {
+            arb_type: ArbType::KalshiOnly,
+            yes_asks: kalshi.asks(Side::Yes),
+            no_asks: kalshi.asks(Side::No),
+            yes_fee: &fees.kalshi,
+            no_fee: &fees.kalshi,
+            yes_tick: kalshi.tick(),
+            no_tick: kalshi.tick(),
+            yes_stamp: limits.confirm(Venue::Kalshi, kalshi.yes_stamp),
+            no_stamp: limits.confirm(Venue::Kalshi, kalshi.no_stamp),
+        },
     ];
 
-    // Find best arbitrage opportunity
-    let mut best: Option<ArbOpportunity> = None;
+    // Find the opportunity with the most total profit
+    let mut best: Option<(Notional, ArbOpportunity)> = None;
+    let mut skipped = None;
 
-    for (arb_type, yes_price, no_price, fee) in opportunities {
+    for combo in opportunities {
+        let arb_type = combo.arb_type;
+        let (yes_venue, no_venue) = arb_type.venues();
+        // Across venues, only identical lines make YES + NO risk-free
+        if yes_venue != no_venue && !market.pair.lines_match() {
+            continue;
+        }
+        let yes_price = combo.yes_asks[0].price;
+        let no_price = combo.no_asks[0].price;
+        let fee = combo.yes_fee.unit_fee(yes_price) + combo.no_fee.unit_fee(no_price);
         let total_cost = yes_price + no_price + fee;
 
-        if total_cost < ARB_THRESHOLD_CENTS {
-            let profit = ARB_THRESHOLD_CENTS as i16 - total_cost as i16;
+        if total_cost < PAIR_PAYOUT {
+            // Never combine a fresh price with an old or unrelated one
+            if let Err(reason) = check_freshness(combo.yes_stamp, combo.no_stamp, limits, now) {
+                let counter = match reason {
+                    Staleness::Stale => &stats.stale_legs,
+                    Staleness::Skewed => &stats.skewed_legs,
+                };
+                counter.fetch_add(1, Ordering::Relaxed);
+                debug!(
+                    "[ARB] Skipping {} on {}: legs {:?}",
+                    arb_type, market.pair.description, reason
+                );
+                skipped = Some(reason);
+                continue;
+            }
+
+            let depth = walk_depth(combo.yes_asks, combo.no_asks, combo.yes_fee, combo.no_fee);
+            let total_profit = depth.profit();
+            // Per-order fee rounding can eat the whole edge on a thin fill
+            if depth.quantity.is_zero() || total_profit <= Notional::ZERO {
+                continue;
+            }
+
+            let profit = PAIR_PAYOUT.saturating_sub(total_cost);
+            let edge_return = EdgeReturn::new(profit.into(), total_cost.into(), settles_in);
+            if !min_edge.accepts(&edge_return) {
+                debug!(
+                    "[ARB] Skipping {} on {}: edge {} ({}) below minimum",
+                    arb_type, market.pair.description, profit, edge_return
+                );
+                continue;
+            }
 
             let arb = ArbOpportunity {
                 market_id: market.pair.id.clone(),
@@ -106,48 +599,523 @@ fn detect_arbitrage(market: &MarketState) -> Option<ArbOpportunity> {
                 total_cost,
                 fee,
                 profit,
+                quantity: depth.quantity,
+                yes_vwap: depth.yes_vwap(),
+                no_vwap: depth.no_vwap(),
+                yes_limit: depth.yes_limit,
+                no_limit: depth.no_limit,
+                yes_tick: combo.yes_tick,
+                no_tick: combo.no_tick,
+                yes_size: combo.yes_asks[0].size,
+                no_size: combo.no_asks[0].size,
+                expected_profit: total_profit,
+                edge_return,
+                fees: *fees,
+                yes_leg: market.pair.leg(yes_venue, Side::Yes),
+                no_leg: market.pair.leg(no_venue, Side::No),
                 timestamp: chrono::Utc::now(),
             };
 
-            if best.is_none() || profit > best.as_ref().unwrap().profit {
-                best = Some(arb);
+            if best.as_ref().is_none_or(|(p, _)| total_profit > *p) {
+                best = Some((total_profit, arb));
             }
         }
     }
 
-    best
+    match (best, skipped) {
+        (Some((_, arb)), _) => Ok(Some(arb)),
+        (None, Some(reason)) => Err(reason),
+        (None, None) => Ok(None),
+    }
 }
 
-/// Execute arbitrage opportunities (dry-run only in MVP)
+/// Execute arbitrage opportunities.
+///
+/// Live pairs go through `engine`; without one (or in dry-run mode) they are
+/// only logged and tracked as hypothetical positions.
 pub async fn execute_arbitrage_loop(
-    mut arb_rx: mpsc::UnboundedReceiver<ArbOpportunity>,
+    mut arb_rx: mpsc::UnboundedReceiver<Opportunity>,
     position_tracker: Arc<RwLock<PositionTracker>>,
+    engine: Option<ExecutionEngine>,
 ) -> Result<()> {
     let dry_run = is_dry_run();
+    let unwind_policy = UnwindPolicy::from_config();
+
+    while let Some(opportunity) = arb_rx.recv().await {
+        match opportunity {
+            Opportunity::Pair(arb) => {
+                let engine = engine.as_ref().map(|e| (e, &unwind_policy));
+                execute_pair(arb, &position_tracker, engine, dry_run).await
+            }
+            Opportunity::Basket(basket) => execute_basket(basket, &position_tracker, dry_run),
+            Opportunity::Middle(middle) => report_middle(&middle),
+        }
+    }
 
-    while let Some(arb) = arb_rx.recv().await {
-        info!(
-            "\n🎯 ARBITRAGE DETECTED!\n   Market: {}\n   Strategy: {}\n   YES: {}¢ | NO: {}¢ | Fee: {}¢\n   Total cost: {}¢\n   Profit: {}¢ ({:.2}%)\n   {}",
-            arb.description,
-            arb.arb_type,
-            arb.yes_price,
-            arb.no_price,
-            arb.fee,
-            arb.total_cost,
-            arb.profit,
-            (arb.profit as f64 / arb.total_cost as f64) * 100.0,
-            if dry_run { "[DRY RUN - Not executing]" } else { "[EXECUTING]" }
+    Ok(())
+}
+
+async fn execute_pair(
+    arb: ArbOpportunity,
+    position_tracker: &RwLock<PositionTracker>,
+    engine: Option<(&ExecutionEngine, &UnwindPolicy)>,
+    dry_run: bool,
+) {
+    info!(
+        "\n🎯 ARBITRAGE DETECTED!\n   Market: {}\n   Strategy: {}\n   Buy: {} + {}\n   YES: {} | NO: {} | Fee: {}\n   Total cost: {}\n   Profit: {} ({})\n   Depth: {} contracts | VWAP YES {:.2}¢ NO {:.2}¢ | Expected profit: {}\n   {}",
+        arb.description,
+        arb.arb_type,
+        arb.yes_leg,
+        arb.no_leg,
+        arb.yes_price,
+        arb.no_price,
+        arb.fee,
+        arb.total_cost,
+        arb.profit,
+        arb.edge_return,
+        arb.quantity,
+        arb.yes_vwap,
+        arb.no_vwap,
+        arb.expected_profit,
+        match (dry_run, engine.is_some()) {
+            (true, _) => "[DRY RUN - Not executing]",
+            (false, false) => "[LIVE - No execution engine, not executing]",
+            (false, true) => "[EXECUTING]",
+        }
+    );
+
+    if dry_run {
+        // In dry-run mode, just log and track hypothetical position
+        let mut tracker = position_tracker.write().unwrap();
+        let quantity = arb
+            .quantity
+            .min(tracker.remaining(&arb.market_id, max_position_size()));
+        let pnl = if quantity == arb.quantity {
+            arb.expected_profit
+        } else {
+            top_of_book_pnl(&arb, quantity)
+        };
+        tracker.record_trade(&arb.market_id, [&arb.yes_leg, &arb.no_leg], quantity, pnl);
+        return;
+    }
+
+    // Live mode without order clients for both venues only logs
+    let Some((engine, unwind_policy)) = engine else {
+        return;
+    };
+    let quantity = {
+        let tracker = position_tracker.read().unwrap();
+        arb.quantity
+            .min(tracker.remaining(&arb.market_id, max_position_size()))
+    };
+    if quantity.is_zero() {
+        return;
+    }
+
+    // Fills reach the position tracker through its order hook as they arrive
+    let report = engine.execute(&arb, quantity).await;
+    for order in std::iter::once(&report.first).chain(&report.second) {
+        if let Some(reason) = &order.reject_reason {
+            warn!("[EXECUTION] {} rejected: {}", order.leg, reason);
+        }
+    }
+    info!(
+        "[EXECUTION] {} | hedged {} of {} | cost {} | locked P&L {}",
+        arb.description,
+        report.hedged,
+        quantity,
+        report.realized_cost(),
+        report.locked_pnl()
+    );
+    if let Some((leg, naked)) = report.unhedged() {
+        warn!(
+            "[EXECUTION] ⚠️  {} contracts of {} left unhedged",
+            naked, leg
         );
+    }
+    let unwound = unwind(engine, unwind_policy, &arb, &report).await;
+    // Unwinding cancels a leg still open, which can turn up late fills
+    let filled = unwound
+        .as_ref()
+        .map_or(report.first.filled, |unwound| unwound.filled);
+    let mut tracker = position_tracker.write().unwrap();
+    if !filled.is_zero() {
+        tracker.record_execution(&arb.market_id, filled, report.locked_pnl());
+    }
+    if let Some(unwound) = unwound {
+        tracker.record_unwind(&unwound.opportunity, unwound.pnl);
+    }
+}
 
+fn execute_basket(
+    basket: BasketOpportunity,
+    position_tracker: &RwLock<PositionTracker>,
+    dry_run: bool,
+) {
+    let legs: Vec<String> = basket
+        .legs
+        .iter()
+        .map(|l| format!("{} @ {}", l.leg, l.price))
+        .collect();
+    info!(
+        "\n🎯 BASKET ARBITRAGE DETECTED!\n   Event: {}\n   Strategy: {} on all {} outcomes\n   Buy: {}\n   Cost: {} | Payout: {} | Expected profit: {} ({} baskets, {})\n   {}",
+        basket.description,
+        basket.side,
+        basket.legs.len(),
+        legs.join(" + "),
+        basket.cost,
+        basket.payout,
+        basket.expected_profit,
+        basket.quantity,
+        basket.edge_return,
         if dry_run {
-            // In dry-run mode, just log and track hypothetical position
-            let mut tracker = position_tracker.write().unwrap();
-            tracker.record_trade(&arb.market_id, arb.profit);
+            "[DRY RUN - Not executing]"
         } else {
-            // In live mode, this would execute actual trades
-            warn!("[EXECUTION] Live trading NOT implemented in MVP");
+            "[LIVE - Baskets are not executed]"
         }
+    );
+
+    if dry_run {
+        let mut tracker = position_tracker.write().unwrap();
+        let quantity = basket
+            .quantity
+            .min(tracker.remaining(&basket.event_id, max_position_size()));
+        // Every leg is priced at the top of its book, so profit scales with
+        // size (up to fee rounding)
+        let pnl = Notional(
+            (basket.expected_profit.0 as i128 * quantity.get() as i128
+                / basket.quantity.get().max(1) as i128) as i64,
+        );
+        let legs: Vec<&Leg> = basket.legs.iter().map(|l| &l.leg).collect();
+        tracker.record_basket(&basket.event_id, &legs, quantity, pnl);
     }
+}
 
-    Ok(())
+/// Log a middle with its outcomes; it is never traded automatically
+fn report_middle(middle: &MiddleOpportunity) {
+    let scenarios: Vec<String> = middle
+        .scenarios
+        .iter()
+        .map(|s| format!("{}: pays {} ({})", s.outcome, s.payout, s.profit))
+        .collect();
+    info!(
+        "\n↔️  {} (lines differ, not risk-free)\n   Market: {}\n   Strategy: {}\n   Buy: {} over {} + {} under {}\n   Total cost: {} | Size: {} contracts\n   {}\n   [REPORT ONLY - Not executing]",
+        middle.kind,
+        middle.description,
+        middle.arb_type,
+        middle.yes_leg,
+        middle.yes_line,
+        middle.no_leg,
+        middle.no_line,
+        middle.total_cost,
+        middle.quantity,
+        scenarios.join("\n   ")
+    );
+}
+
+/// PnL of `quantity` pairs at the opportunity's top-of-book prices, with
+/// each leg's fee charged as one order
+fn top_of_book_pnl(arb: &ArbOpportunity, quantity: Contracts) -> Notional {
+    let (yes_venue, no_venue) = arb.arb_type.venues();
+    let fees = arb
+        .fees
+        .venue(yes_venue)
+        .order_fee(arb.yes_price, quantity)
+        .saturating_add(arb.fees.venue(no_venue).order_fee(arb.no_price, quantity));
+    let spread = PAIR_PAYOUT.saturating_sub(arb.yes_price + arb.no_price);
+    quantity
+        .notional(spread)
+        .unwrap_or(Notional::ZERO)
+        .saturating_sub(fees)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::MinEdge;
+    use crate::fees::{FeeSchedule, KalshiFee};
+    use crate::lines::{Line, MiddleKind};
+    use crate::types::SharedBook;
+
+    /// Flat per-contract fee
+    #[derive(Debug)]
+    struct FlatFee(Price);
+
+    impl FeeModel for FlatFee {
+        fn unit_fee(&self, _: Price) -> Price {
+            self.0
+        }
+    }
+
+    const FREE: &FeeSchedule = &FeeSchedule::Free;
+
+    fn c(cents: u32) -> Price {
+        Price::from_cents(cents)
+    }
+
+    fn quote(cents: u32, received_at: Instant) -> (Vec<PriceLevel>, BookStamp) {
+        (
+            vec![PriceLevel {
+                price: c(cents),
+                size: Contracts(10),
+            }],
+            BookStamp {
+                exchange_ts: None,
+                received_at,
+            },
+        )
+    }
+
+    fn set(book: &SharedBook, side: Side, q: (Vec<PriceLevel>, BookStamp)) {
+        book.update(|book| {
+            book.set_asks(side, q.0.clone());
+            book.set_stamp(side, q.1);
+        });
+    }
+
+    #[test]
+    fn test_stale_legs_are_skipped_and_counted() {
+        let market = MarketState::new(crate::config::get_hardcoded_markets().remove(0));
+        let limits = FreshnessLimits {
+            max_age: Duration::from_secs(2),
+            max_skew: Duration::from_secs(1),
+            feeds: FeedLiveness::default(),
+        };
+        let min_edges = MinEdgeDefaults::from_config();
+        let stats = DetectionStats::new();
+        let now = Instant::now();
+        let old = now - Duration::from_secs(60);
+
+        // Kalshi YES 42 + Poly NO 50 is an arb, but the Poly leg is a minute old
+        set(&market.kalshi, Side::Yes, quote(42, now));
+        set(&market.kalshi, Side::No, quote(60, now));
+        set(&market.poly, Side::Yes, quote(60, now));
+        set(&market.poly, Side::No, quote(50, old));
+
+        assert!(matches!(
+            detect_arbitrage(&market, &limits, &min_edges, &stats),
+            Err(Staleness::Stale)
+        ));
+        assert_eq!(stats.stale_legs.load(Ordering::Relaxed), 1);
+
+        set(&market.poly, Side::No, quote(50, now));
+        let arb = detect_arbitrage(&market, &limits, &min_edges, &stats)
+            .unwrap()
+            .unwrap();
+        assert_eq!(arb.arb_type, ArbType::KalshiYesPolyNo);
+    }
+
+    #[test]
+    fn test_unchanged_books_on_live_feeds_stay_fresh() {
+        let market = MarketState::new(crate::config::get_hardcoded_markets().remove(0));
+        let health = ConnectionHealth::new();
+        let limits = FreshnessLimits {
+            max_age: Duration::from_secs(2),
+            max_skew: Duration::from_secs(1),
+            feeds: FeedLiveness::default(),
+        };
+        let min_edges = MinEdgeDefaults::from_config();
+        let stats = DetectionStats::new();
+        let now = Instant::now();
+        let old = now - Duration::from_secs(60);
+
+        // Both books last changed a minute ago
+        set(&market.kalshi, Side::Yes, quote(42, old));
+        set(&market.kalshi, Side::No, quote(60, old));
+        set(&market.poly, Side::Yes, quote(60, old));
+        set(&market.poly, Side::No, quote(50, old));
+        assert!(matches!(
+            detect_arbitrage(&market, &limits, &min_edges, &stats),
+            Err(Staleness::Stale)
+        ));
+
+        // ...but both connections have delivered frames since
+        health.record_frame(Venue::Kalshi, now);
+        health.record_frame(Venue::Polymarket, now);
+        let limits = limits.with_feeds(&health);
+        let arb = detect_arbitrage(&market, &limits, &min_edges, &stats)
+            .unwrap()
+            .unwrap();
+        assert_eq!(arb.arb_type, ArbType::KalshiYesPolyNo);
+    }
+
+    #[test]
+    fn test_mismatched_lines_are_middles_not_arbs() {
+        let mut pair = crate::config::get_hardcoded_markets().remove(0);
+        pair.kalshi_line = Some(Line::from_points(220.5));
+        pair.poly_line = Some(Line::from_points(221.5));
+        let market = MarketState::new(pair);
+        let limits = FreshnessLimits::from_config();
+        let min_edges = MinEdgeDefaults::from_config();
+        let stats = DetectionStats::new();
+        let now = Instant::now();
+
+        set(&market.kalshi, Side::Yes, quote(42, now));
+        set(&market.kalshi, Side::No, quote(60, now));
+        set(&market.poly, Side::Yes, quote(60, now));
+        set(&market.poly, Side::No, quote(50, now));
+
+        assert!(matches!(
+            detect_arbitrage(&market, &limits, &min_edges, &stats),
+            Ok(None)
+        ));
+        let found = detect_middle(&market, &limits, &stats).unwrap();
+        assert_eq!(found.len(), 2);
+        // Poly YES 60 + Kalshi NO 60 costs over $1 but is still reported
+        assert_eq!(found[0].kind, MiddleKind::Gap);
+        assert!(found[0].total_cost >= Price::ONE);
+        let middle = &found[1];
+        assert_eq!(middle.arb_type, ArbType::KalshiYesPolyNo);
+        assert_eq!(middle.kind, MiddleKind::Middle);
+        // Over 220.5 + under 221.5 pays twice on exactly 221
+        assert_eq!(middle.scenarios[1].payout, Price(2 * Price::SCALE));
+    }
+
+    #[test]
+    fn test_edges_below_market_minimum_are_skipped() {
+        let mut pair = crate::config::get_hardcoded_markets().remove(0);
+        pair.min_edge = Some(MinEdge {
+            profit: Notional::from_cents(10),
+            ..MinEdge::default()
+        });
+        let market = MarketState::new(pair);
+        let limits = FreshnessLimits::from_config();
+        let min_edges = MinEdgeDefaults::from_config();
+        let stats = DetectionStats::new();
+        let now = Instant::now();
+
+        // Kalshi YES 42 + Poly NO 50 + ~1.7¢ fee leaves ~6.3¢ per contract
+        set(&market.kalshi, Side::Yes, quote(42, now));
+        set(&market.kalshi, Side::No, quote(60, now));
+        set(&market.poly, Side::Yes, quote(60, now));
+        set(&market.poly, Side::No, quote(50, now));
+        assert!(matches!(
+            detect_arbitrage(&market, &limits, &min_edges, &stats),
+            Ok(None)
+        ));
+
+        let mut pair = market.pair.clone();
+        pair.min_edge = Some(MinEdge {
+            return_bps: 500,
+            ..MinEdge::default()
+        });
+        pair.settles_at = None;
+        let market = MarketState::new(pair);
+        set(&market.kalshi, Side::Yes, quote(42, now));
+        set(&market.kalshi, Side::No, quote(60, now));
+        set(&market.poly, Side::Yes, quote(60, now));
+        set(&market.poly, Side::No, quote(50, now));
+        let arb = detect_arbitrage(&market, &limits, &min_edges, &stats)
+            .unwrap()
+            .unwrap();
+        // 6.29¢ on 93.71¢
+        assert_eq!(arb.edge_return.return_bps, 671);
+        assert_eq!(arb.edge_return.annualized_bps, None);
+    }
+
+    #[test]
+    fn test_walk_depth_stops_at_unprofitable_levels() {
+        let yes = [
+            PriceLevel {
+                price: c(40),
+                size: Contracts(10),
+            },
+            PriceLevel {
+                price: c(44),
+                size: Contracts(50),
+            },
+            PriceLevel {
+                price: c(50),
+                size: Contracts(100),
+            },
+        ];
+        let no = [
+            PriceLevel {
+                price: c(52),
+                size: Contracts(30),
+            },
+            PriceLevel {
+                price: c(54),
+                size: Contracts(100),
+            },
+        ];
+
+        // 40+52, 44+52, 44+54 all clear $1.00 after 1¢ fees; 50+54 does not
+        let fill = walk_depth(&yes, &no, FREE, &FlatFee(Price::CENT));
+        assert_eq!(fill.quantity, Contracts(60));
+        assert_eq!(fill.yes_cost, Notional::from_cents(10 * 40 + 50 * 44));
+        assert_eq!(fill.no_cost, Notional::from_cents(30 * 52 + 30 * 54));
+        assert_eq!(fill.fees, Notional::from_cents(60));
+        assert_eq!(fill.profit(), Notional::from_cents(6000 - 2600 - 3180 - 60));
+        assert!((fill.yes_vwap() - 2600.0 / 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_walk_depth_large_books() {
+        // Sizes far beyond the old u16 limit
+        let yes = [PriceLevel {
+            price: c(40),
+            size: Contracts(5_000_000),
+        }];
+        let no = [PriceLevel {
+            price: c(55),
+            size: Contracts(3_000_000),
+        }];
+        let fill = walk_depth(&yes, &no, FREE, FREE);
+        assert_eq!(fill.quantity, Contracts(3_000_000));
+        assert_eq!(fill.profit(), Notional::from_cents(3_000_000 * 5));
+
+        // Notional that cannot be represented stops the walk instead of wrapping
+        let yes = [PriceLevel {
+            price: c(40),
+            size: Contracts(u64::MAX),
+        }];
+        let no = [PriceLevel {
+            price: c(55),
+            size: Contracts(u64::MAX),
+        }];
+        assert_eq!(walk_depth(&yes, &no, FREE, FREE).quantity, Contracts::ZERO);
+    }
+
+    #[test]
+    fn test_sub_cent_edge_is_detected() {
+        // 0.995 + 0.004 leaves a tenth of a cent that whole-cent prices rounded away
+        let level = |price| PriceLevel {
+            price: Price::parse_decimal(price).unwrap(),
+            size: Contracts(1000),
+        };
+        let (yes, no) = ([level("0.995")], [level("0.004")]);
+        let fill = walk_depth(&yes, &no, FREE, FREE);
+        assert_eq!(fill.quantity, Contracts(1000));
+        assert_eq!(fill.profit(), Notional::from_cents(100));
+    }
+
+    #[test]
+    fn test_kalshi_fees_rounded_per_level() {
+        let yes = [
+            PriceLevel {
+                price: c(45),
+                size: Contracts(100),
+            },
+            PriceLevel {
+                price: c(46),
+                size: Contracts(100),
+            },
+        ];
+        let no = [PriceLevel {
+            price: c(50),
+            size: Contracts(150),
+        }];
+        let kalshi = KalshiFee::taker();
+
+        // YES fills 100 @ 45 and 50 @ 46, NO fills 150 @ 50 in one order:
+        // $1.7325 -> $1.74, $0.8694 -> $0.87, $2.625 -> $2.63
+        let fill = walk_depth(&yes, &no, &kalshi, &kalshi);
+        assert_eq!(fill.quantity, Contracts(150));
+        assert_eq!(fill.fees, Notional::from_cents(174 + 87 + 263));
+        // Rounded per contract at 2¢ a leg, the 46¢ level no longer pays
+        let fill = walk_depth(&yes, &no, &kalshi.per_contract(), &kalshi.per_contract());
+        assert_eq!(fill.quantity, Contracts(100));
+        assert_eq!(fill.fees, Notional::from_cents(400));
+    }
 }
@@ -0,0 +1,552 @@
+//! Venue-agnostic market data feed plumbing.
+//!
+//! Each venue adapter implements [`FeedClient`]: it builds its subscribe
+//! frames and decodes raw frames into normalized [`FeedEvent`]s. The single
+//! connection driver [`run_feed`] owns the WebSocket (connect, subscribe,
+//! ping/pong, dispatch) and applies events to the shared market state.
+
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, error, info, warn};
+
+use crate::config::liveness;
+use crate::registry::{MarketRegistry, RoutingIndex};
+use crate::supervisor::{ConnectionHealth, ConnectionState};
+use crate::types::{BookStamp, MarketState, Orderbook, Price, PriceLevel, Side, Venue};
+
+/// Normalized event decoded from a venue frame
+#[derive(Debug, Clone)]
+pub enum FeedEvent {
+    /// Replace the ask ladder for one side of a market
+    Asks {
+        market: Arc<MarketState>,
+        side: Side,
+        levels: Vec<PriceLevel>,
+        /// Minimum price increment, when the venue has reported one
+        tick_size: Option<Price>,
+        /// Exchange event time, when the frame carries one
+        exchange_ts: Option<chrono::DateTime<chrono::Utc>>,
+    },
+    /// The market's book can no longer be trusted and must be cleared
+    Invalidate { market: Arc<MarketState> },
+    /// Frame to send back to the venue (e.g. a resubscribe)
+    Send(String),
+}
+
+/// Notification that a market's book changed
+#[derive(Debug, Clone)]
+pub struct BookUpdate {
+    pub market: Arc<MarketState>,
+    /// When the frame that changed the book was received
+    pub received_at: Instant,
+}
+
+/// A market data adapter for one venue
+pub trait FeedClient: Send {
+    /// Venue this adapter connects to
+    fn venue(&self) -> Venue;
+
+    /// WebSocket endpoint
+    fn url(&self) -> &str;
+
+    /// Reset per-connection state and build the subscribe frames for every
+    /// market in `routes`, which the adapter keeps for resolving frames
+    fn subscribe(&mut self, routes: Arc<RoutingIndex>) -> Vec<String>;
+
+    /// Decode one text frame into normalized events
+    fn decode(&mut self, text: &str) -> Result<Vec<FeedEvent>>;
+
+    /// Application-level ping text frame; `None` sends a WebSocket ping
+    fn ping_frame(&self) -> Option<String> {
+        None
+    }
+}
+
+/// A connection judged dead by the liveness checks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LivenessFailure {
+    /// No frame of any kind arrived within the read-idle timeout
+    ReadIdle { silent_for: Duration },
+    /// A market that had been updating stopped while the socket stayed up
+    MarketSilent {
+        market_id: String,
+        silent_for: Duration,
+    },
+}
+
+impl std::fmt::Display for LivenessFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LivenessFailure::ReadIdle { silent_for } => {
+                write!(f, "no data received for {:.0}s", silent_for.as_secs_f64())
+            }
+            LivenessFailure::MarketSilent {
+                market_id,
+                silent_for,
+            } => write!(
+                f,
+                "market {} silent for {:.0}s",
+                market_id,
+                silent_for.as_secs_f64()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LivenessFailure {}
+
+/// Tracks when each market last updated on the current connection
+#[derive(Debug, Clone)]
+pub struct MarketWatchdog {
+    window: Duration,
+    last_update: HashMap<String, Instant>,
+}
+
+impl MarketWatchdog {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last_update: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, market_id: &str, at: Instant) {
+        match self.last_update.get_mut(market_id) {
+            Some(last) => *last = at,
+            None => {
+                self.last_update.insert(market_id.to_string(), at);
+            }
+        }
+    }
+
+    /// Forget a market (e.g. invalidated and waiting for a new snapshot)
+    pub fn forget(&mut self, market_id: &str) {
+        self.last_update.remove(market_id);
+    }
+
+    /// The longest-silent market past the window, if any.
+    /// Only markets that have updated at least once are expected to be active.
+    pub fn check(&self, now: Instant) -> Option<LivenessFailure> {
+        self.last_update
+            .iter()
+            .map(|(id, at)| (id, now.saturating_duration_since(*at)))
+            .filter(|(_, silent_for)| *silent_for > self.window)
+            .max_by_key(|(_, silent_for)| *silent_for)
+            .map(|(id, silent_for)| LivenessFailure::MarketSilent {
+                market_id: id.clone(),
+                silent_for,
+            })
+    }
+}
+
+/// Run one WebSocket connection for `client` until it closes or errors.
+///
+/// Marks the venue `Live` in `health` once subscribed, and returns cleanly
+/// when the tracked market set changes so the caller reconnects and
+/// resubscribes.
+pub async fn run_feed<C: FeedClient>(
+    client: &mut C,
+    registry: &MarketRegistry,
+    health: &ConnectionHealth,
+    updates: &mpsc::UnboundedSender<BookUpdate>,
+) -> Result<()> {
+    let venue = client.venue();
+    let tag = venue.tag();
+
+    info!("[{}] Connecting to WebSocket: {}", tag, client.url());
+
+    let (ws_stream, _) = connect_async(client.url())
+        .await
+        .with_context(|| format!("Failed to connect to {} WebSocket", venue))?;
+
+    info!("[{}] ✅ Connected to WebSocket", tag);
+
+    let (mut write, mut read) = ws_stream.split();
+
+    // Subscribe to orderbook updates for all tracked markets
+    let version = registry.version();
+    let routes = registry.routes();
+    let market_count = routes.market_count();
+
+    let subscribe_msgs = client.subscribe(routes);
+    for subscribe_msg in subscribe_msgs {
+        write
+            .send(Message::Text(subscribe_msg))
+            .await
+            .context("Failed to send subscribe message")?;
+    }
+    info!("[{}] Subscribed to {} markets", tag, market_count);
+    health.set(venue, ConnectionState::Live);
+
+    let liveness = liveness(venue);
+    let mut ping_timer = tokio::time::interval(liveness.ping_interval);
+    ping_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut watchdog = MarketWatchdog::new(liveness.market_idle_timeout);
+    let mut last_frame = Instant::now();
+
+    // Read messages
+    loop {
+        let idle_deadline = last_frame + liveness.read_idle_timeout;
+
+        let msg = tokio::select! {
+            msg = read.next() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            _ = ping_timer.tick() => {
+                let ping = match client.ping_frame() {
+                    Some(text) => Message::Text(text),
+                    None => Message::Ping(Vec::new()),
+                };
+                write.send(ping).await.context("Failed to send ping")?;
+
+                if let Some(failure) = watchdog.check(Instant::now()) {
+                    health.raise(venue, &failure);
+                    return Err(failure.into());
+                }
+                if registry.version() != version {
+                    info!("[{}] Tracked markets changed, resubscribing", tag);
+                    return Ok(());
+                }
+                continue;
+            }
+            _ = tokio::time::sleep_until(idle_deadline.into()) => {
+                let failure = LivenessFailure::ReadIdle {
+                    silent_for: last_frame.elapsed(),
+                };
+                health.raise(venue, &failure);
+                return Err(failure.into());
+            }
+        };
+        last_frame = Instant::now();
+        health.record_frame(venue, last_frame);
+
+        match msg {
+            Ok(Message::Text(text)) => {
+                let received_at = last_frame;
+                let events = match client.decode(&text) {
+                    Ok(events) => events,
+                    Err(e) => {
+                        health.record_malformed(venue);
+                        warn!(
+                            "[{}] Rejected frame: {:#} | {}",
+                            tag,
+                            e,
+                            truncate(&text, 200)
+                        );
+                        continue;
+                    }
+                };
+                for event in &events {
+                    match event {
+                        FeedEvent::Asks { market, .. } => {
+                            watchdog.record(&market.pair.id, received_at)
+                        }
+                        FeedEvent::Invalidate { market } => watchdog.forget(&market.pair.id),
+                        FeedEvent::Send(_) => {}
+                    }
+                }
+                for outbound in apply_events(venue, events, received_at, updates) {
+                    write
+                        .send(Message::Text(outbound))
+                        .await
+                        .context("Failed to send message")?;
+                }
+            }
+            Ok(Message::Ping(data)) => {
+                debug!("[{}] Received ping, sending pong", tag);
+                if let Err(e) = write.send(Message::Pong(data)).await {
+                    error!("[{}] Failed to send pong: {}", tag, e);
+                }
+            }
+            Ok(Message::Close(_)) => {
+                warn!("[{}] WebSocket closed by server", tag);
+                break;
+            }
+            Err(e) => {
+                // Surface transport errors so the supervisor can classify them
+                return Err(anyhow::Error::new(e).context("WebSocket read failed"));
+            }
+            _ => {}
+        }
+    }
+
+    warn!("[{}] WebSocket disconnected", tag);
+    Ok(())
+}
+
+/// One change to a book, collected from a frame
+enum BookChange {
+    Asks {
+        side: Side,
+        levels: Vec<PriceLevel>,
+        tick_size: Option<Price>,
+        stamp: BookStamp,
+    },
+    Clear,
+}
+
+impl BookChange {
+    fn apply(&self, book: &mut Orderbook) {
+        match self {
+            BookChange::Asks {
+                side,
+                levels,
+                tick_size,
+                stamp,
+            } => {
+                book.set_asks(*side, levels.clone());
+                book.set_stamp(*side, *stamp);
+                if tick_size.is_some() {
+                    book.tick_size = *tick_size;
+                }
+            }
+            BookChange::Clear => *book = Orderbook::default(),
+        }
+    }
+}
+
+/// Apply decoded events to the shared market state and notify `updates`
+/// once per changed market, after all of the frame's events are applied.
+///
+/// Every change a frame makes to one market's book is published in a single
+/// update, so detection never loads a book with only some of them (e.g. a
+/// Kalshi snapshot's new YES ladder next to its old NO ladder).
+///
+/// Returns the frames that must be sent back to the venue.
+pub fn apply_events(
+    venue: Venue,
+    events: Vec<FeedEvent>,
+    received_at: Instant,
+    updates: &mpsc::UnboundedSender<BookUpdate>,
+) -> Vec<String> {
+    let mut outbound = Vec::new();
+    let mut changed: Vec<(Arc<MarketState>, Vec<BookChange>)> = Vec::new();
+
+    for event in events {
+        match event {
+            FeedEvent::Asks {
+                market: market_state,
+                side,
+                mut levels,
+                tick_size,
+                exchange_ts,
+            } => {
+                // A level between ticks cannot be traded at
+                let tick = tick_size.unwrap_or_else(|| market_state.book(venue).load().tick());
+                let depth = levels.len();
+                levels.retain(|level| level.price.is_on_tick(tick));
+                if levels.len() < depth {
+                    warn!(
+                        "[{}] {} | dropped {} {} ask levels off the {} tick",
+                        venue.tag(),
+                        market_state.pair.description,
+                        depth - levels.len(),
+                        side,
+                        tick
+                    );
+                }
+                let best = levels.first().copied().unwrap_or_default();
+                debug!(
+                    "[{}] {} | {} ask: {} ({}) | depth {}",
+                    venue.tag(),
+                    market_state.pair.description,
+                    side,
+                    best.price,
+                    best.size,
+                    levels.len()
+                );
+                let stamp = BookStamp {
+                    exchange_ts,
+                    received_at,
+                };
+                let change = BookChange::Asks {
+                    side,
+                    levels,
+                    tick_size,
+                    stamp,
+                };
+                record_change(&mut changed, market_state, change);
+            }
+            FeedEvent::Invalidate { market } => {
+                record_change(&mut changed, market, BookChange::Clear);
+            }
+            FeedEvent::Send(frame) => outbound.push(frame),
+        }
+    }
+
+    for (market, changes) in changed {
+        market.book(venue).update(|book| {
+            for change in &changes {
+                change.apply(book);
+            }
+        });
+        // Detection may have shut down; the books are still current
+        let _ = updates.send(BookUpdate {
+            market,
+            received_at,
+        });
+    }
+
+    outbound
+}
+
+/// The first `max` characters of `text`
+pub(crate) fn truncate(text: &str, max: usize) -> &str {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+/// Queue `change` for `market`, in frame order
+fn record_change(
+    changed: &mut Vec<(Arc<MarketState>, Vec<BookChange>)>,
+    market: Arc<MarketState>,
+    change: BookChange,
+) {
+    match changed.iter_mut().find(|(m, _)| Arc::ptr_eq(m, &market)) {
+        Some((_, changes)) => changes.push(change),
+        None => changed.push((market, vec![change])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Contracts, NO_PRICE};
+
+    #[test]
+    fn test_watchdog_flags_only_silent_active_markets() {
+        let start = Instant::now();
+        let mut watchdog = MarketWatchdog::new(Duration::from_secs(60));
+        watchdog.record("quiet", start);
+        watchdog.record("busy", start);
+
+        let later = start + Duration::from_secs(90);
+        watchdog.record("busy", later);
+        assert_eq!(
+            watchdog.check(later),
+            Some(LivenessFailure::MarketSilent {
+                market_id: "quiet".into(),
+                silent_for: Duration::from_secs(90),
+            })
+        );
+
+        watchdog.forget("quiet");
+        assert_eq!(watchdog.check(later), None);
+    }
+
+    #[test]
+    fn test_apply_events_notifies_each_market_once() {
+        let market = Arc::new(MarketState::new(
+            crate::config::get_hardcoded_markets().remove(0),
+        ));
+        let level = PriceLevel {
+            price: Price::from_cents(45),
+            size: Contracts(10),
+        };
+        let asks = |side| FeedEvent::Asks {
+            market: market.clone(),
+            side,
+            levels: vec![level],
+            tick_size: Some(Price::CENT),
+            exchange_ts: None,
+        };
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let received_at = Instant::now();
+
+        let outbound = apply_events(
+            Venue::Kalshi,
+            vec![
+                asks(Side::Yes),
+                asks(Side::No),
+                FeedEvent::Send("ack".into()),
+            ],
+            received_at,
+            &tx,
+        );
+
+        assert_eq!(outbound, vec!["ack".to_string()]);
+        let update = rx.try_recv().unwrap();
+        assert!(Arc::ptr_eq(&update.market, &market));
+        assert_eq!(update.received_at, received_at);
+        assert!(rx.try_recv().is_err());
+        // Both sides were applied before the notification went out
+        let book = market.kalshi.load();
+        assert_eq!((book.yes_ask, book.no_ask), (level.price, level.price));
+        assert_eq!(book.tick(), Price::CENT);
+
+        // A frame's changes are applied in order, in one publish
+        apply_events(
+            Venue::Kalshi,
+            vec![
+                FeedEvent::Invalidate {
+                    market: market.clone(),
+                },
+                asks(Side::No),
+            ],
+            received_at,
+            &tx,
+        );
+        let book = market.kalshi.load();
+        assert_eq!((book.yes_ask, book.no_ask), (NO_PRICE, level.price));
+        assert!(book.yes_stamp.is_none());
+    }
+
+    #[test]
+    fn test_off_tick_levels_are_dropped() {
+        let market = Arc::new(MarketState::new(
+            crate::config::get_hardcoded_markets().remove(0),
+        ));
+        let tick = Price::parse_decimal("0.001");
+        let level = |price| PriceLevel {
+            price: Price::parse_decimal(price).unwrap(),
+            size: Contracts(10),
+        };
+        let asks = |tick_size, levels| FeedEvent::Asks {
+            market: market.clone(),
+            side: Side::Yes,
+            levels,
+            tick_size,
+            exchange_ts: None,
+        };
+        let (tx, _rx) = mpsc::unbounded_channel();
+
+        // 0.523 trades on a 0.001 tick; 0.5235 falls between ticks
+        let levels = vec![level("0.523"), level("0.5235"), level("0.53")];
+        apply_events(
+            Venue::Polymarket,
+            vec![asks(tick, levels.clone())],
+            Instant::now(),
+            &tx,
+        );
+        let book = market.poly.load();
+        assert_eq!(book.tick(), tick.unwrap());
+        assert_eq!(book.yes_asks, vec![levels[0], levels[2]]);
+
+        // Without a reported tick, the book's own tick still applies
+        apply_events(
+            Venue::Polymarket,
+            vec![asks(None, levels.clone())],
+            Instant::now(),
+            &tx,
+        );
+        assert_eq!(market.poly.load().yes_asks, vec![levels[0], levels[2]]);
+
+        // An unknown tick is taken to be a cent
+        market.poly.clear();
+        apply_events(
+            Venue::Polymarket,
+            vec![asks(None, levels)],
+            Instant::now(),
+            &tx,
+        );
+        assert_eq!(market.poly.load().yes_ask, Price::from_cents(53));
+    }
+}
@@ -0,0 +1,260 @@
+//! Trading fee models.
+//!
+//! Each leg of an opportunity is priced with the [`FeeModel`] its venue uses
+//! for that market. Pairs pick their schedules in config via [`PairFees`], so
+//! a discounted Kalshi series or a fee-enabled Polymarket market only needs a
+//! config change, not a detection change.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Contracts, Notional, Price, Venue};
+
+/// Kalshi taker fee rate: 0.07 × P × (1 - P) per contract
+pub const KALSHI_TAKER_RATE_BPS: u32 = 700;
+
+/// Kalshi maker fee rate: 0.0175 × P × (1 - P) per contract
+pub const KALSHI_MAKER_RATE_BPS: u32 = 175;
+
+/// Fees charged by a venue for buying contracts
+pub trait FeeModel: std::fmt::Debug + Send + Sync {
+    /// Fee per contract at `price` before any per-order rounding.
+    ///
+    /// Detection uses this to decide whether one more contract still pays.
+    fn unit_fee(&self, price: Price) -> Price;
+
+    /// Fee charged on one order of `quantity` contracts at `price`
+    fn order_fee(&self, price: Price, quantity: Contracts) -> Notional {
+        quantity
+            .notional(self.unit_fee(price))
+            .unwrap_or(Notional(i64::MAX))
+    }
+}
+
+/// Whether our order adds or removes liquidity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Liquidity {
+    Taker,
+    Maker,
+}
+
+/// Where a fee is rounded up to the cent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeRounding {
+    /// Once on the whole order (how Kalshi charges)
+    PerOrder,
+    /// On every contract (conservative; the old MVP behaviour)
+    PerContract,
+}
+
+/// Kalshi fee: rate × multiplier × C × P × (1 - P), rounded up to the cent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KalshiFee {
+    pub liquidity: Liquidity,
+    /// Series fee multiplier in percent (100 = standard, 50 = half fees)
+    pub multiplier_pct: u32,
+    pub rounding: FeeRounding,
+}
+
+impl KalshiFee {
+    pub const fn taker() -> Self {
+        Self {
+            liquidity: Liquidity::Taker,
+            multiplier_pct: 100,
+            rounding: FeeRounding::PerOrder,
+        }
+    }
+
+    pub const fn maker() -> Self {
+        Self {
+            liquidity: Liquidity::Maker,
+            ..Self::taker()
+        }
+    }
+
+    /// Apply a series fee multiplier, in percent
+    pub const fn with_multiplier(self, multiplier_pct: u32) -> Self {
+        Self {
+            multiplier_pct,
+            ..self
+        }
+    }
+
+    /// Round every contract's fee up to the cent instead of the order's
+    pub const fn per_contract(self) -> Self {
+        Self {
+            rounding: FeeRounding::PerContract,
+            ..self
+        }
+    }
+
+    fn rate_bps(&self) -> u32 {
+        match self.liquidity {
+            Liquidity::Taker => KALSHI_TAKER_RATE_BPS,
+            Liquidity::Maker => KALSHI_MAKER_RATE_BPS,
+        }
+    }
+
+    /// Exact fee on `quantity` contracts, in price units rounded up
+    fn exact_units(&self, price: Price, quantity: u64) -> u128 {
+        if price.is_zero() || price >= Price::ONE {
+            return 0;
+        }
+        let p = price.units() as u128;
+        let scale = Price::SCALE as u128;
+        let rate = self.rate_bps() as u128 * self.multiplier_pct as u128;
+        (rate * quantity as u128 * p * (scale - p)).div_ceil(10_000 * 100 * scale)
+    }
+}
+
+impl FeeModel for KalshiFee {
+    fn unit_fee(&self, price: Price) -> Price {
+        let fee = Price(self.exact_units(price, 1) as u32);
+        match self.rounding {
+            FeeRounding::PerOrder => fee,
+            FeeRounding::PerContract => fee.ceil_to(Price::CENT),
+        }
+    }
+
+    fn order_fee(&self, price: Price, quantity: Contracts) -> Notional {
+        match self.rounding {
+            FeeRounding::PerContract => quantity
+                .notional(self.unit_fee(price))
+                .unwrap_or(Notional(i64::MAX)),
+            FeeRounding::PerOrder => {
+                let cent = Price::CENT.units() as u128;
+                let units = self.exact_units(price, quantity.get()).div_ceil(cent) * cent;
+                Notional(i64::try_from(units).unwrap_or(i64::MAX))
+            }
+        }
+    }
+}
+
+/// Polymarket fee on fee-enabled markets: rate × min(P, 1 - P) per share
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolymarketFee {
+    pub fee_rate_bps: u32,
+}
+
+impl PolymarketFee {
+    fn exact_units(&self, price: Price, quantity: u64) -> u128 {
+        let p = price.units().min(Price::SCALE) as u128;
+        let distance = p.min(Price::SCALE as u128 - p);
+        (self.fee_rate_bps as u128 * quantity as u128 * distance).div_ceil(10_000)
+    }
+}
+
+impl FeeModel for PolymarketFee {
+    fn unit_fee(&self, price: Price) -> Price {
+        Price(self.exact_units(price, 1) as u32)
+    }
+
+    fn order_fee(&self, price: Price, quantity: Contracts) -> Notional {
+        Notional(i64::try_from(self.exact_units(price, quantity.get())).unwrap_or(i64::MAX))
+    }
+}
+
+/// Fee schedule for one venue of a market pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum FeeSchedule {
+    Free,
+    Kalshi(KalshiFee),
+    Polymarket(PolymarketFee),
+}
+
+impl FeeModel for FeeSchedule {
+    fn unit_fee(&self, price: Price) -> Price {
+        match self {
+            FeeSchedule::Free => Price::ZERO,
+            FeeSchedule::Kalshi(fee) => fee.unit_fee(price),
+            FeeSchedule::Polymarket(fee) => fee.unit_fee(price),
+        }
+    }
+
+    fn order_fee(&self, price: Price, quantity: Contracts) -> Notional {
+        match self {
+            FeeSchedule::Free => Notional::ZERO,
+            FeeSchedule::Kalshi(fee) => fee.order_fee(price, quantity),
+            FeeSchedule::Polymarket(fee) => fee.order_fee(price, quantity),
+        }
+    }
+}
+
+/// Fee schedules for both venues of a market pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairFees {
+    pub kalshi: FeeSchedule,
+    pub poly: FeeSchedule,
+}
+
+impl Default for PairFees {
+    /// Kalshi taker fees; Polymarket markets are fee-free unless enabled
+    fn default() -> Self {
+        Self {
+            kalshi: FeeSchedule::Kalshi(KalshiFee::taker()),
+            poly: FeeSchedule::Free,
+        }
+    }
+}
+
+impl PairFees {
+    pub fn venue(&self, venue: Venue) -> &FeeSchedule {
+        match venue {
+            Venue::Kalshi => &self.kalshi,
+            Venue::Polymarket => &self.poly,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kalshi_fee_cents() {
+        let fee = KalshiFee::taker().per_contract();
+        // At 50 cents: ceil(7 * 50 * 50 / 10000) = ceil(1.75) = 2
+        assert_eq!(fee.unit_fee(Price::from_cents(50)), Price::from_cents(2));
+        // At 10 cents: ceil(7 * 10 * 90 / 10000) = ceil(0.63) = 1
+        assert_eq!(fee.unit_fee(Price::from_cents(10)), Price::from_cents(1));
+        // At 0 and 100: no fee
+        assert_eq!(fee.unit_fee(Price::ZERO), Price::ZERO);
+        assert_eq!(fee.unit_fee(Price::ONE), Price::ZERO);
+    }
+
+    #[test]
+    fn test_order_rounding_and_multipliers() {
+        let fifty = Price::from_cents(50);
+        let hundred = Contracts(100);
+
+        // 100 × $0.0175 = $1.75 rounded once, vs 100 × 2¢ rounded per contract
+        let taker = KalshiFee::taker();
+        assert_eq!(taker.order_fee(fifty, hundred), Notional::from_cents(175));
+        assert_eq!(
+            taker.per_contract().order_fee(fifty, hundred),
+            Notional::from_cents(200)
+        );
+        // A single contract still pays a whole cent
+        assert_eq!(
+            taker.order_fee(fifty, Contracts(1)),
+            Notional::from_cents(2)
+        );
+
+        assert_eq!(
+            KalshiFee::maker().order_fee(fifty, hundred),
+            Notional::from_cents(44)
+        );
+        let half = taker.with_multiplier(50);
+        assert_eq!(half.order_fee(fifty, hundred), Notional::from_cents(88));
+
+        // 2% of min(P, 1 - P): 100 × 0.02 × $0.30
+        let poly = FeeSchedule::Polymarket(PolymarketFee { fee_rate_bps: 200 });
+        assert_eq!(
+            poly.order_fee(Price::from_cents(70), hundred),
+            Notional::from_cents(60)
+        );
+        assert_eq!(FeeSchedule::Free.order_fee(fifty, hundred), Notional::ZERO);
+    }
+}
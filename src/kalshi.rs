@@ -1,177 +1,489 @@
 //! Kalshi WebSocket client for orderbook updates.
 //!
 //! MVP version: connects to public WebSocket feed, no authentication.
-//! Does NOT support order execution (would require RSA signature generation).
-
-use anyhow::{Context, Result};
-use futures_util::{SinkExt, StreamExt};
-use serde_json::Value;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
-use tracing::{debug, error, info, warn};
-
-use crate::types::{MarketState, Orderbook, PriceCents, SizeCents};
-
-/// Kalshi WebSocket URL (demo/public endpoint)
-const KALSHI_WS_URL: &str = "wss://demo-api.kalshi.co/trade-api/ws/v2";
-
-/// Run Kalshi WebSocket connection
-pub async fn run_kalshi_ws(
-    markets: Arc<RwLock<HashMap<String, Arc<MarketState>>>>,
-) -> Result<()> {
-    info!("[KALSHI] Connecting to WebSocket: {}", KALSHI_WS_URL);
-
-    let (ws_stream, _) = connect_async(KALSHI_WS_URL)
-        .await
-        .context("Failed to connect to Kalshi WebSocket")?;
-
-    info!("[KALSHI] ✅ Connected to WebSocket");
-
-    let (mut write, mut read) = ws_stream.split();
-
-    // Subscribe to orderbook updates for all tracked markets
-    let markets_guard = markets.read().unwrap();
-    let tickers: Vec<String> = markets_guard
-        .values()
-        .map(|m| m.pair.kalshi_ticker.clone())
-        .collect();
-    drop(markets_guard);
-
-    if !tickers.is_empty() {
-        let subscribe_msg = serde_json::json!({
-            "type": "subscribe",
-            "channels": [{
-                "name": "orderbook_delta",
-                "tickers": tickers,
-            }]
-        });
+//! Orders go through the signed REST client in `kalshi_rest`.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::feed::{FeedClient, FeedEvent};
+use crate::kalshi_messages::{
+    Envelope, ErrorMessage, MessageType, OrderbookDelta, OrderbookSnapshot, Subscribed, Ticker,
+    Trade,
+};
+use crate::orderbook::KalshiBook;
+use crate::registry::RoutingIndex;
+use crate::types::{MarketState, Price, Side, Venue};
 
-        write
-            .send(Message::Text(subscribe_msg.to_string()))
-            .await
-            .context("Failed to send subscribe message")?;
+/// Outcome of checking a message's sequence number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SeqCheck {
+    InOrder,
+    /// One or more messages were skipped
+    Gap {
+        expected: u64,
+        got: u64,
+    },
+    /// Duplicate or out-of-order message
+    Stale {
+        last: u64,
+        got: u64,
+    },
+}
+
+/// Kalshi `orderbook_delta` feed adapter
+#[derive(Debug, Default)]
+pub struct KalshiFeed {
+    /// WebSocket endpoint
+    url: String,
+    /// Ticker -> market routing for the current subscription
+    routes: Arc<RoutingIndex>,
+    /// L2 books keyed by ticker (rebuilt from snapshots)
+    books: HashMap<String, KalshiBook>,
+    /// Last sequence number seen per subscription id
+    last_seq: HashMap<u64, u64>,
+    /// Tickers of each subscribe command not yet confirmed, by command id
+    pending: HashMap<u64, Vec<String>>,
+    /// Tickers whose updates arrive on each subscription id
+    sid_tickers: HashMap<u64, HashSet<String>>,
+    /// Tickers resubscribed after their subscription's sequence broke, with
+    /// the replaced subscription id, until their fresh snapshot arrives
+    resyncing: HashMap<String, u64>,
+    /// Id for the next command sent to the server
+    next_cmd_id: u64,
+}
 
-        info!("[KALSHI] Subscribed to {} markets", tickers.len());
+impl KalshiFeed {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            ..Self::default()
+        }
     }
 
-    // Read messages
-    while let Some(msg) = read.next().await {
-        match msg {
-            Ok(Message::Text(text)) => {
-                if let Err(e) = handle_kalshi_message(&text, &markets) {
-                    warn!("[KALSHI] Error handling message: {}", e);
-                }
+    fn next_id(&mut self) -> u64 {
+        self.next_cmd_id += 1;
+        self.next_cmd_id
+    }
+
+    /// Build an `orderbook_delta` subscribe command for `tickers`.
+    /// The server answers with an orderbook_snapshot per ticker, then deltas.
+    fn subscribe_message(&mut self, tickers: &[String]) -> String {
+        let id = self.next_id();
+        self.pending.insert(id, tickers.to_vec());
+        serde_json::json!({
+            "id": id,
+            "cmd": "subscribe",
+            "params": {
+                "channels": ["orderbook_delta"],
+                "market_tickers": tickers,
             }
-            Ok(Message::Ping(data)) => {
-                debug!("[KALSHI] Received ping, sending pong");
-                if let Err(e) = write.send(Message::Pong(data)).await {
-                    error!("[KALSHI] Failed to send pong: {}", e);
+        })
+        .to_string()
+    }
+
+    /// Check `seq` against the last sequence number seen on `sid`
+    fn check_seq(&mut self, sid: u64, seq: u64) -> SeqCheck {
+        let check = match self.last_seq.get(&sid) {
+            None => SeqCheck::InOrder,
+            Some(&last) if seq == last + 1 => SeqCheck::InOrder,
+            Some(&last) if seq <= last => return SeqCheck::Stale { last, got: seq },
+            Some(&last) => SeqCheck::Gap {
+                expected: last + 1,
+                got: seq,
+            },
+        };
+        self.last_seq.insert(sid, seq);
+        check
+    }
+
+    /// Sequence-check and apply an orderbook snapshot or delta
+    fn decode_orderbook(&mut self, envelope: &Envelope) -> Result<Vec<FeedEvent>> {
+        let (sid, seq) = envelope.sequence()?;
+
+        // Validate the body before it can advance the sequence; a rejected
+        // frame then shows up as a gap and forces a resync
+        if envelope.kind == MessageType::OrderbookSnapshot {
+            let msg = envelope.body::<OrderbookSnapshot>()?;
+            Ok(match self.check_seq(sid, seq) {
+                SeqCheck::InOrder => {
+                    // A fresh snapshot ends the ticker's resync
+                    self.resyncing.remove(msg.market_ticker.as_ref());
+                    self.handle_orderbook_snapshot(msg)
                 }
+                broken => self.sequence_broken(sid, broken),
+            })
+        } else {
+            let msg = envelope.body::<OrderbookDelta>()?;
+            // Deltas from a subscription being replaced are meaningless
+            if self.resyncing.get(msg.market_ticker.as_ref()) == Some(&sid) {
+                return Ok(Vec::new());
             }
-            Ok(Message::Close(_)) => {
-                warn!("[KALSHI] WebSocket closed by server");
-                break;
-            }
-            Err(e) => {
-                error!("[KALSHI] WebSocket error: {}", e);
-                break;
+            Ok(match self.check_seq(sid, seq) {
+                SeqCheck::InOrder => self.handle_orderbook_delta(msg),
+                broken => self.sequence_broken(sid, broken),
+            })
+        }
+    }
+
+    /// Log a broken sequence and resync the subscription
+    fn sequence_broken(&mut self, sid: u64, check: SeqCheck) -> Vec<FeedEvent> {
+        match check {
+            SeqCheck::Gap { expected, got } => warn!(
+                "[KALSHI] Sequence gap on sid {}: expected {}, got {}",
+                sid, expected, got
+            ),
+            SeqCheck::Stale { last, got } => warn!(
+                "[KALSHI] Out-of-order message on sid {}: last {}, got {}",
+                sid, last, got
+            ),
+            SeqCheck::InOrder => return Vec::new(),
+        }
+        self.resync(sid)
+    }
+
+    /// Drop all state for a subscription whose sequence broke, invalidate the
+    /// affected books, and replace it with a fresh subscription (which starts
+    /// with new snapshots).
+    fn resync(&mut self, sid: u64) -> Vec<FeedEvent> {
+        self.last_seq.remove(&sid);
+
+        // Every ticker the subscription asked for, snapshot received or not.
+        // If its confirmation never arrived, every ticker no other
+        // subscription covers.
+        let tickers: Vec<String> = match self.sid_tickers.remove(&sid) {
+            Some(tickers) => tickers.into_iter().collect(),
+            None => self
+                .routes
+                .kalshi_tickers()
+                .filter(|t| !self.sid_tickers.values().any(|sub| sub.contains(*t)))
+                .cloned()
+                .collect(),
+        };
+
+        let mut events = Vec::new();
+        for ticker in &tickers {
+            self.resyncing.insert(ticker.clone(), sid);
+            self.books.remove(ticker);
+            if let Some(market) = self.routes.kalshi(ticker) {
+                events.push(FeedEvent::Invalidate {
+                    market: market.clone(),
+                });
             }
-            _ => {}
         }
+
+        warn!(
+            "[KALSHI] Resyncing subscription {} ({} markets invalidated)",
+            sid,
+            tickers.len()
+        );
+
+        let unsubscribe = serde_json::json!({
+            "id": self.next_id(),
+            "cmd": "unsubscribe",
+            "params": { "sids": [sid] }
+        })
+        .to_string();
+        events.push(FeedEvent::Send(unsubscribe));
+
+        if !tickers.is_empty() {
+            let subscribe = self.subscribe_message(&tickers);
+            events.push(FeedEvent::Send(subscribe));
+        }
+        events
+    }
+
+    /// Handle full orderbook snapshot (sent once per ticker after subscribing)
+    fn handle_orderbook_snapshot(&mut self, msg: OrderbookSnapshot) -> Vec<FeedEvent> {
+        let ticker = msg.market_ticker.as_ref();
+        let market = match self.routes.kalshi(ticker) {
+            Some(market) => market.clone(),
+            None => return Vec::new(), // Market not tracked
+        };
+
+        let book = self.books.entry(ticker.to_string()).or_default();
+        book.apply_snapshot(&msg.yes, &msg.no);
+
+        book_events(market, book, None)
     }
 
-    warn!("[KALSHI] WebSocket disconnected");
-    Ok(())
+    /// Handle incremental orderbook delta (one price level on one side)
+    fn handle_orderbook_delta(&mut self, msg: OrderbookDelta) -> Vec<FeedEvent> {
+        let ticker = msg.market_ticker.as_ref();
+        let market = match self.routes.kalshi(ticker) {
+            Some(market) => market.clone(),
+            None => return Vec::new(), // Market not tracked
+        };
+
+        // Deltas are only meaningful on top of a snapshot
+        let book = match self.books.get_mut(ticker) {
+            Some(book) => book,
+            None => {
+                debug!("[KALSHI] Delta for {} before snapshot, ignoring", ticker);
+                return Vec::new();
+            }
+        };
+        book.apply_delta(msg.side.into(), msg.price, msg.delta);
+
+        book_events(market, book, msg.ts)
+    }
 }
 
-/// Handle incoming Kalshi message
-fn handle_kalshi_message(
-    text: &str,
-    markets: &Arc<RwLock<HashMap<String, Arc<MarketState>>>>,
-) -> Result<()> {
-    let msg: Value = serde_json::from_str(text).context("Failed to parse JSON")?;
-
-    // Check message type
-    let msg_type = msg
-        .get("type")
-        .and_then(|v| v.as_str())
-        .unwrap_or("unknown");
-
-    match msg_type {
-        "orderbook_delta" => {
-            handle_orderbook_delta(&msg, markets)?;
+impl FeedClient for KalshiFeed {
+    fn venue(&self) -> Venue {
+        Venue::Kalshi
+    }
+
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn subscribe(&mut self, routes: Arc<RoutingIndex>) -> Vec<String> {
+        // Sequence numbers and books do not survive a reconnect
+        *self = Self {
+            url: std::mem::take(&mut self.url),
+            next_cmd_id: self.next_cmd_id,
+            routes,
+            ..Self::default()
+        };
+
+        let tickers: Vec<String> = self.routes.kalshi_tickers().cloned().collect();
+        if tickers.is_empty() {
+            return Vec::new();
         }
-        "subscribed" => {
-            debug!("[KALSHI] Subscription confirmed");
+        vec![self.subscribe_message(&tickers)]
+    }
+
+    fn decode(&mut self, text: &str) -> Result<Vec<FeedEvent>> {
+        let envelope = Envelope::parse(text)?;
+
+        match envelope.kind {
+            MessageType::OrderbookSnapshot | MessageType::OrderbookDelta => {
+                return self.decode_orderbook(&envelope);
+            }
+            MessageType::Ticker => {
+                let msg = envelope.body::<Ticker>()?;
+                debug!(
+                    "[KALSHI] Ticker {}: last {:?} bid {:?} ask {:?}",
+                    msg.market_ticker, msg.price, msg.yes_bid, msg.yes_ask
+                );
+            }
+            MessageType::Trade => {
+                let msg = envelope.body::<Trade>()?;
+                debug!(
+                    "[KALSHI] Trade {}: {} @ {} ({:?} taker)",
+                    msg.market_ticker, msg.count, msg.yes_price, msg.taker_side
+                );
+            }
+            MessageType::Subscribed => {
+                let msg = envelope.body::<Subscribed>()?;
+                // The confirmation carries the command id: its tickers now
+                // arrive on this sid
+                if let Some(tickers) = envelope.id.and_then(|id| self.pending.remove(&id)) {
+                    self.sid_tickers
+                        .insert(msg.sid, tickers.into_iter().collect());
+                }
+                debug!(
+                    "[KALSHI] Subscription confirmed: {} (sid {})",
+                    msg.channel, msg.sid
+                );
+            }
+            MessageType::Unsubscribed => {
+                debug!("[KALSHI] Unsubscribed sid {:?}", envelope.sid);
+            }
+            MessageType::Error => {
+                let msg = envelope.body::<ErrorMessage>()?;
+                warn!(
+                    "[KALSHI] Error for command {:?}: {} (code {})",
+                    envelope.id, msg.msg, msg.code
+                );
+            }
+            MessageType::Unknown => {
+                debug!("[KALSHI] Unknown message type");
+            }
         }
-        "error" => {
-            warn!("[KALSHI] Error message: {:?}", msg);
+        Ok(Vec::new())
+    }
+}
+
+/// Both derived ask ladders for a ticker's book
+fn book_events(
+    market: Arc<MarketState>,
+    book: &KalshiBook,
+    exchange_ts: Option<DateTime<Utc>>,
+) -> Vec<FeedEvent> {
+    vec![
+        FeedEvent::Asks {
+            market: market.clone(),
+            side: Side::Yes,
+            levels: book.asks(Side::Yes),
+            tick_size: Some(Price::CENT),
+            exchange_ts,
+        },
+        FeedEvent::Asks {
+            market,
+            side: Side::No,
+            levels: book.asks(Side::No),
+            tick_size: Some(Price::CENT),
+            exchange_ts,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::MarketRegistry;
+    use crate::types::{Contracts, PriceLevel};
+
+    fn test_feed() -> KalshiFeed {
+        let mut pair = crate::config::get_hardcoded_markets().remove(0);
+        pair.id = "test-market".into();
+        pair.kalshi_ticker = "TEST-TICKER".into();
+        let registry = MarketRegistry::new();
+        registry.add(pair);
+        let mut feed = KalshiFeed::new("ws://localhost");
+        feed.subscribe(registry.routes());
+        feed
+    }
+
+    #[test]
+    fn test_sequence_gap_detection() {
+        let mut feed = KalshiFeed::default();
+        assert_eq!(feed.check_seq(1, 1), SeqCheck::InOrder);
+        assert_eq!(feed.check_seq(1, 2), SeqCheck::InOrder);
+        assert_eq!(
+            feed.check_seq(1, 5),
+            SeqCheck::Gap {
+                expected: 3,
+                got: 5
+            }
+        );
+        assert_eq!(feed.check_seq(1, 4), SeqCheck::Stale { last: 5, got: 4 });
+        // Independent subscriptions are tracked separately
+        assert_eq!(feed.check_seq(2, 10), SeqCheck::InOrder);
+    }
+
+    #[test]
+    fn test_snapshot_and_delta_frames() {
+        let mut feed = test_feed();
+
+        let snapshot = r#"{"type":"orderbook_snapshot","sid":7,"seq":1,"msg":{"market_ticker":"TEST-TICKER","yes":[[40,10]],"no":[[55,20],[50,5]]}}"#;
+        let events = feed.decode(snapshot).unwrap();
+        match &events[0] {
+            FeedEvent::Asks {
+                market,
+                side,
+                levels,
+                tick_size,
+                exchange_ts,
+            } => {
+                assert_eq!(market.pair.id, "test-market");
+                assert_eq!(*tick_size, Some(Price::CENT));
+                assert_eq!(*side, Side::Yes);
+                assert_eq!(
+                    levels,
+                    &vec![
+                        PriceLevel {
+                            price: Price::from_cents(45),
+                            size: Contracts(20)
+                        },
+                        PriceLevel {
+                            price: Price::from_cents(50),
+                            size: Contracts(5)
+                        },
+                    ]
+                );
+                assert_eq!(*exchange_ts, None);
+            }
+            other => panic!("unexpected event {:?}", other),
         }
-        _ => {
-            debug!("[KALSHI] Unknown message type: {}", msg_type);
+
+        let delta = r#"{"type":"orderbook_delta","sid":7,"seq":2,"msg":{"market_ticker":"TEST-TICKER","price":55,"delta":-20,"side":"no"}}"#;
+        let events = feed.decode(delta).unwrap();
+        match &events[0] {
+            FeedEvent::Asks { side, levels, .. } => {
+                assert_eq!(*side, Side::Yes);
+                assert_eq!(levels[0].price, Price::from_cents(50));
+            }
+            other => panic!("unexpected event {:?}", other),
         }
     }
 
-    Ok(())
-}
+    #[test]
+    fn test_gap_invalidates_book_and_resubscribes() {
+        let mut feed = test_feed();
+
+        let snapshot = r#"{"type":"orderbook_snapshot","sid":7,"seq":1,"msg":{"market_ticker":"TEST-TICKER","yes":[[40,10]],"no":[[55,20]]}}"#;
+        let gap = r#"{"type":"orderbook_delta","sid":7,"seq":3,"msg":{"market_ticker":"TEST-TICKER","price":55,"delta":-5,"side":"no"}}"#;
+
+        feed.decode(snapshot).unwrap();
+        let events = feed.decode(gap).unwrap();
+        assert!(
+            matches!(&events[0], FeedEvent::Invalidate { market } if market.pair.id == "test-market")
+        );
+        assert!(matches!(&events[1], FeedEvent::Send(m) if m.contains("unsubscribe")));
+        assert!(matches!(&events[2], FeedEvent::Send(m) if m.contains("TEST-TICKER")));
+
+        // Late frames from the old subscription are dropped
+        assert!(feed.decode(gap).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resync_resubscribes_tickers_without_snapshot() {
+        let mut feed = test_feed();
+
+        // The gap swallowed TEST-TICKER's snapshot: no book was ever built
+        let subscribed =
+            r#"{"type":"subscribed","id":1,"msg":{"channel":"orderbook_delta","sid":7}}"#;
+        let delta = r#"{"type":"orderbook_delta","sid":7,"seq":2,"msg":{"market_ticker":"TEST-TICKER","price":55,"delta":-5,"side":"no"}}"#;
+        let gap = r#"{"type":"orderbook_delta","sid":7,"seq":4,"msg":{"market_ticker":"TEST-TICKER","price":55,"delta":-5,"side":"no"}}"#;
+
+        feed.decode(subscribed).unwrap();
+        feed.decode(delta).unwrap();
+        let events = feed.decode(gap).unwrap();
+        let resubscribe = events.iter().any(|e| {
+            matches!(e, FeedEvent::Send(m) if m.contains("\"subscribe\"") && m.contains("TEST-TICKER"))
+        });
+        assert!(resubscribe, "{:?}", events);
+    }
+
+    #[test]
+    fn test_ticker_resyncs_again_after_its_fresh_snapshot() {
+        let mut feed = test_feed();
+        let snapshot = |seq: u64| {
+            format!(
+                r#"{{"type":"orderbook_snapshot","sid":7,"seq":{},"msg":{{"market_ticker":"TEST-TICKER","yes":[[40,10]],"no":[[55,20]]}}}}"#,
+                seq
+            )
+        };
+        let delta = |seq: u64| {
+            format!(
+                r#"{{"type":"orderbook_delta","sid":7,"seq":{},"msg":{{"market_ticker":"TEST-TICKER","price":55,"delta":-5,"side":"no"}}}}"#,
+                seq
+            )
+        };
+        let resubscribes = |events: &[FeedEvent]| {
+            events
+                .iter()
+                .any(|e| matches!(e, FeedEvent::Send(m) if m.contains("\"subscribe\"")))
+        };
+
+        feed.decode(&snapshot(1)).unwrap();
+        assert!(resubscribes(&feed.decode(&delta(3)).unwrap()));
 
-/// Handle orderbook delta update
-fn handle_orderbook_delta(
-    msg: &Value,
-    markets: &Arc<RwLock<HashMap<String, Arc<MarketState>>>>,
-) -> Result<()> {
-    let ticker = msg
-        .get("ticker")
-        .and_then(|v| v.as_str())
-        .context("Missing ticker")?;
-
-    let markets_guard = markets.read().unwrap();
-    let market_state = markets_guard
-        .values()
-        .find(|m| m.pair.kalshi_ticker == ticker);
-
-    let market_state = match market_state {
-        Some(m) => m.clone(),
-        None => return Ok(()), // Market not tracked
-    };
-    drop(markets_guard);
-
-    // Parse yes_ask and no_ask (prices are in cents: 1-99)
-    let yes_ask = msg
-        .get("yes_ask")
-        .and_then(|v| v.as_i64())
-        .unwrap_or(0) as PriceCents;
-
-    let no_ask = msg
-        .get("no_ask")
-        .and_then(|v| v.as_i64())
-        .unwrap_or(0) as PriceCents;
-
-    // Parse sizes (in cents)
-    let yes_size = msg
-        .get("yes_ask_size")
-        .and_then(|v| v.as_i64())
-        .unwrap_or(0) as SizeCents;
-
-    let no_size = msg
-        .get("no_ask_size")
-        .and_then(|v| v.as_i64())
-        .unwrap_or(0) as SizeCents;
-
-    // Update market state
-    {
-        let mut book = market_state.kalshi.write().unwrap();
-        book.yes_ask = yes_ask;
-        book.no_ask = no_ask;
-        book.yes_size = yes_size;
-        book.no_size = no_size;
-    }
-
-    debug!(
-        "[KALSHI] {} | YES: {}¢ ({}¢) | NO: {}¢ ({}¢)",
-        market_state.pair.description, yes_ask, yes_size, no_ask, no_size
-    );
-
-    Ok(())
+        // The resubscription (command 3) is confirmed on the same sid and
+        // starts over with a snapshot
+        let subscribed =
+            r#"{"type":"subscribed","id":3,"msg":{"channel":"orderbook_delta","sid":7}}"#;
+        feed.decode(subscribed).unwrap();
+        feed.decode(&snapshot(1)).unwrap();
+        assert!(!feed.decode(&delta(2)).unwrap().is_empty());
+
+        // A second gap resyncs again
+        assert!(resubscribes(&feed.decode(&delta(4)).unwrap()));
+    }
 }
@@ -0,0 +1,237 @@
+//! Kalshi Trade API v2 WebSocket message models.
+//!
+//! Frames are decoded in two steps: a borrowed [`Envelope`] carrying the
+//! type, subscription id and sequence number, then the channel-specific `msg`
+//! body, which stays a raw slice of the frame until its type is known. No
+//! `serde_json::Value` tree is built, and a frame with a missing or
+//! out-of-range field is rejected rather than defaulted.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::{self, Deserializer, Visitor};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::types::{Price, Side};
+
+/// Message types on the channels we consume
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageType {
+    OrderbookSnapshot,
+    OrderbookDelta,
+    Ticker,
+    Trade,
+    Subscribed,
+    Unsubscribed,
+    Error,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Fields common to every frame
+#[derive(Debug, Deserialize)]
+pub struct Envelope<'a> {
+    #[serde(rename = "type")]
+    pub kind: MessageType,
+    /// Command id this frame answers (control messages only)
+    pub id: Option<u64>,
+    /// Subscription id (data messages only)
+    pub sid: Option<u64>,
+    /// Per-subscription sequence number (orderbook messages only)
+    pub seq: Option<u64>,
+    #[serde(borrow)]
+    pub msg: Option<&'a RawValue>,
+}
+
+impl<'a> Envelope<'a> {
+    pub fn parse(text: &'a str) -> Result<Self> {
+        serde_json::from_str(text).context("Malformed Kalshi frame")
+    }
+
+    /// Subscription id and sequence number, required on orderbook messages
+    pub fn sequence(&self) -> Result<(u64, u64)> {
+        let sid = self.sid.context("orderbook message missing sid")?;
+        let seq = self.seq.context("orderbook message missing seq")?;
+        Ok((sid, seq))
+    }
+
+    /// Decode the `msg` body as `T`
+    pub fn body<T: Deserialize<'a>>(&self) -> Result<T> {
+        let raw = self
+            .msg
+            .with_context(|| format!("{:?} message missing msg", self.kind))?;
+        serde_json::from_str(raw.get()).with_context(|| format!("Invalid {:?} body", self.kind))
+    }
+}
+
+/// Contract side as sent on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WireSide {
+    Yes,
+    No,
+}
+
+impl From<WireSide> for Side {
+    fn from(side: WireSide) -> Self {
+        match side {
+            WireSide::Yes => Side::Yes,
+            WireSide::No => Side::No,
+        }
+    }
+}
+
+/// `orderbook_snapshot`: resting bids on both sides of one market
+#[derive(Debug, Deserialize)]
+pub struct OrderbookSnapshot<'a> {
+    #[serde(borrow)]
+    pub market_ticker: Cow<'a, str>,
+    /// `[price, size]` YES bids (omitted when the side is empty)
+    #[serde(default, deserialize_with = "levels")]
+    pub yes: Vec<(Price, u64)>,
+    /// `[price, size]` NO bids (omitted when the side is empty)
+    #[serde(default, deserialize_with = "levels")]
+    pub no: Vec<(Price, u64)>,
+}
+
+/// `orderbook_delta`: signed size change at one price level
+#[derive(Debug, Deserialize)]
+pub struct OrderbookDelta<'a> {
+    #[serde(borrow)]
+    pub market_ticker: Cow<'a, str>,
+    #[serde(deserialize_with = "price")]
+    pub price: Price,
+    pub delta: i64,
+    pub side: WireSide,
+    #[serde(default, deserialize_with = "timestamp")]
+    pub ts: Option<DateTime<Utc>>,
+}
+
+/// `ticker`: top-of-book and last price summary
+#[derive(Debug, Deserialize)]
+pub struct Ticker<'a> {
+    #[serde(borrow)]
+    pub market_ticker: Cow<'a, str>,
+    /// Last traded price, in cents
+    pub price: Option<u32>,
+    pub yes_bid: Option<u32>,
+    pub yes_ask: Option<u32>,
+    pub volume: Option<i64>,
+    pub open_interest: Option<i64>,
+    #[serde(default, deserialize_with = "timestamp")]
+    pub ts: Option<DateTime<Utc>>,
+}
+
+/// `trade`: one public execution
+#[derive(Debug, Deserialize)]
+pub struct Trade<'a> {
+    #[serde(borrow)]
+    pub market_ticker: Cow<'a, str>,
+    #[serde(deserialize_with = "price")]
+    pub yes_price: Price,
+    #[serde(deserialize_with = "price")]
+    pub no_price: Price,
+    pub count: u64,
+    pub taker_side: WireSide,
+    #[serde(default, deserialize_with = "timestamp")]
+    pub ts: Option<DateTime<Utc>>,
+}
+
+/// `subscribed`: a subscribe command was accepted
+#[derive(Debug, Deserialize)]
+pub struct Subscribed<'a> {
+    #[serde(borrow)]
+    pub channel: Cow<'a, str>,
+    pub sid: u64,
+}
+
+/// `error`: a command was rejected
+#[derive(Debug, Deserialize)]
+pub struct ErrorMessage<'a> {
+    pub code: i64,
+    #[serde(borrow)]
+    pub msg: Cow<'a, str>,
+}
+
+/// Validate a price in cents: binary contracts trade strictly between 0 and 100
+fn check_price<E: de::Error>(price: u64) -> Result<Price, E> {
+    match u32::try_from(price) {
+        Ok(p) if (1..=99).contains(&p) => Ok(Price::from_cents(p)),
+        _ => Err(E::custom(format_args!("price {} outside 1..=99", price))),
+    }
+}
+
+fn price<'de, D: Deserializer<'de>>(d: D) -> Result<Price, D::Error> {
+    check_price(u64::deserialize(d)?)
+}
+
+/// `[[price, size], ...]` with every price validated
+fn levels<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<(Price, u64)>, D::Error> {
+    Vec::<(u64, u64)>::deserialize(d)?
+        .into_iter()
+        .map(|(p, size)| Ok((check_price(p)?, size)))
+        .collect()
+}
+
+/// Exchange timestamp: RFC 3339 string or Unix seconds
+fn timestamp<'de, D: Deserializer<'de>>(d: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+    struct TsVisitor;
+
+    impl<'de> Visitor<'de> for TsVisitor {
+        type Value = Option<DateTime<Utc>>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an RFC 3339 timestamp or Unix seconds")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            DateTime::parse_from_rfc3339(s)
+                .map(|t| Some(t.with_timezone(&Utc)))
+                .map_err(E::custom)
+        }
+
+        fn visit_u64<E: de::Error>(self, secs: u64) -> Result<Self::Value, E> {
+            let secs = i64::try_from(secs).map_err(E::custom)?;
+            self.visit_i64(secs)
+        }
+
+        fn visit_i64<E: de::Error>(self, secs: i64) -> Result<Self::Value, E> {
+            DateTime::from_timestamp(secs, 0)
+                .map(Some)
+                .ok_or_else(|| E::custom(format_args!("timestamp {} out of range", secs)))
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+    }
+
+    d.deserialize_any(TsVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_malformed_bodies() {
+        let bad_price = r#"{"type":"orderbook_delta","sid":1,"seq":2,"msg":{"market_ticker":"T","price":100,"delta":5,"side":"yes"}}"#;
+        let envelope = Envelope::parse(bad_price).unwrap();
+        let err = envelope.body::<OrderbookDelta>().unwrap_err();
+        assert!(format!("{:#}", err).contains("price 100 outside 1..=99"));
+
+        let no_ticker = r#"{"type":"orderbook_snapshot","sid":1,"seq":1,"msg":{"yes":[[40,10]]}}"#;
+        let envelope = Envelope::parse(no_ticker).unwrap();
+        let err = envelope.body::<OrderbookSnapshot>().unwrap_err();
+        assert!(format!("{:#}", err).contains("missing field `market_ticker`"));
+
+        let no_seq = r#"{"type":"orderbook_delta","sid":1,"msg":{}}"#;
+        assert!(Envelope::parse(no_seq).unwrap().sequence().is_err());
+
+        let future = r#"{"type":"fill","sid":3,"msg":{}}"#;
+        assert_eq!(Envelope::parse(future).unwrap().kind, MessageType::Unknown);
+    }
+}
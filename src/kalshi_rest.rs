@@ -0,0 +1,763 @@
+//! Authenticated Kalshi REST trading client.
+//!
+//! Every request is signed with the account's API key: the
+//! `KALSHI-ACCESS-SIGNATURE` header is an RSA-PSS (SHA-256) signature over
+//! the millisecond timestamp, the HTTP method and the request path (without
+//! its query string), sent alongside the key id and the timestamp. Only the
+//! base URL differs between production, the demo exchange and a local mock
+//! server.
+//!
+//! [`KalshiRestClient`] implements [`OrderApi`], so the execution engine can
+//! place and cancel orders through it.
+
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use reqwest::{Method, StatusCode};
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::pss::BlindedSigningKey;
+use rsa::signature::{RandomizedSigner, SignatureEncoding};
+use rsa::RsaPrivateKey;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::time::Duration;
+
+use crate::engine::OrderApi;
+use crate::orders::{Order, OrderAction, OrderEvent, TimeInForce};
+use crate::types::{Contracts, Notional, Price, Side, Venue};
+
+/// Typical time from sending an order to its response
+const TYPICAL_LATENCY: Duration = Duration::from_millis(150);
+
+/// Page size for list endpoints (Kalshi's maximum is 1000)
+const PAGE_LIMIT: u32 = 200;
+
+/// Signs requests with a Kalshi API key
+pub struct KalshiSigner {
+    key_id: String,
+    key: BlindedSigningKey<Sha256>,
+}
+
+impl std::fmt::Debug for KalshiSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KalshiSigner")
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
+impl KalshiSigner {
+    pub fn new(key_id: impl Into<String>, key: RsaPrivateKey) -> Self {
+        Self {
+            key_id: key_id.into(),
+            key: BlindedSigningKey::new(key),
+        }
+    }
+
+    /// Signer from a PEM private key, PKCS#1 (as Kalshi issues them) or PKCS#8
+    pub fn from_pem(key_id: impl Into<String>, pem: &str) -> Result<Self> {
+        let key = RsaPrivateKey::from_pkcs1_pem(pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs8_pem(pem))
+            .context("Invalid Kalshi private key (expected PKCS#1 or PKCS#8 PEM)")?;
+        Ok(Self::new(key_id, key))
+    }
+
+    pub fn from_pem_file(key_id: impl Into<String>, path: &str) -> Result<Self> {
+        let pem = std::fs::read_to_string(path)
+            .with_context(|| format!("Reading Kalshi private key {}", path))?;
+        Self::from_pem(key_id, &pem)
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Base64 signature over `timestamp_ms`, `method` and `path`
+    pub fn sign(&self, timestamp_ms: i64, method: &Method, path: &str) -> String {
+        let message = format!("{}{}{}", timestamp_ms, method.as_str(), path);
+        let signature = self
+            .key
+            .sign_with_rng(&mut rand::thread_rng(), message.as_bytes());
+        BASE64.encode(signature.to_bytes())
+    }
+}
+
+/// An error response from the API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Kalshi {} {}: {}",
+            self.status.as_u16(),
+            self.code,
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[derive(Debug, Default, Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    error: ErrorDetail,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ErrorDetail {
+    #[serde(default)]
+    code: String,
+    #[serde(default)]
+    message: String,
+}
+
+/// Kalshi's names for sides and actions
+mod wire {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::orders::OrderAction;
+    use crate::types::Side;
+
+    pub mod side {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(side: &Side, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_str(match side {
+                Side::Yes => "yes",
+                Side::No => "no",
+            })
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Side, D::Error> {
+            match <&str>::deserialize(d)? {
+                "yes" => Ok(Side::Yes),
+                "no" => Ok(Side::No),
+                other => Err(serde::de::Error::custom(format_args!(
+                    "unknown side {}",
+                    other
+                ))),
+            }
+        }
+    }
+
+    pub mod action {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(action: &OrderAction, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_str(match action {
+                OrderAction::Buy => "buy",
+                OrderAction::Sell => "sell",
+            })
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<OrderAction, D::Error> {
+            match <&str>::deserialize(d)? {
+                "buy" => Ok(OrderAction::Buy),
+                "sell" => Ok(OrderAction::Sell),
+                other => Err(serde::de::Error::custom(format_args!(
+                    "unknown action {}",
+                    other
+                ))),
+            }
+        }
+    }
+}
+
+/// Whole cents of a limit price; Kalshi does not take sub-cent limits
+fn whole_cents(price: Price) -> Result<u32> {
+    if !price.is_on_tick(Price::CENT) || !(Price::CENT..Price::ONE).contains(&price) {
+        bail!(
+            "Kalshi limit {} is not a whole cent between 1¢ and 99¢",
+            price
+        );
+    }
+    Ok(price.units() / Price::CENT.units())
+}
+
+/// `POST /portfolio/orders` body: a limit order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CreateOrderRequest {
+    pub ticker: String,
+    pub client_order_id: String,
+    #[serde(with = "wire::side")]
+    pub side: Side,
+    #[serde(with = "wire::action")]
+    pub action: OrderAction,
+    pub count: u64,
+    #[serde(rename = "type")]
+    pub order_type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yes_price: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_price: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_in_force: Option<&'static str>,
+    /// Unix seconds; set for good-till-time orders
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_ts: Option<i64>,
+}
+
+impl CreateOrderRequest {
+    /// Request placing `order`, whose leg must be a Kalshi ticker
+    pub fn limit(order: &Order) -> Result<Self> {
+        if order.venue() != Venue::Kalshi {
+            bail!("{} is not a Kalshi order", order.client_order_id);
+        }
+        let cents = whole_cents(order.price)?;
+        let (time_in_force, expiration_ts) = match order.time_in_force {
+            TimeInForce::ImmediateOrCancel => (Some("immediate_or_cancel"), None),
+            TimeInForce::FillOrKill => (Some("fill_or_kill"), None),
+            TimeInForce::GoodTillCancelled => (Some("good_till_canceled"), None),
+            TimeInForce::GoodTillTime(at) => (None, Some(at.timestamp())),
+        };
+        Ok(Self {
+            ticker: order.leg.instrument.clone(),
+            client_order_id: order.client_order_id.clone(),
+            side: order.leg.side,
+            action: order.action,
+            count: order.quantity.get(),
+            order_type: "limit",
+            yes_price: (order.leg.side == Side::Yes).then_some(cents),
+            no_price: (order.leg.side == Side::No).then_some(cents),
+            time_in_force,
+            expiration_ts,
+        })
+    }
+}
+
+/// `POST /portfolio/orders/{order_id}/amend` body: a resting order's new
+/// price and total count
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AmendOrderRequest {
+    pub ticker: String,
+    #[serde(with = "wire::side")]
+    pub side: Side,
+    #[serde(with = "wire::action")]
+    pub action: OrderAction,
+    pub client_order_id: String,
+    pub updated_client_order_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yes_price: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_price: Option<u32>,
+    pub count: u64,
+}
+
+impl AmendOrderRequest {
+    /// Move `order` to `price` for `quantity` contracts in total, renaming it
+    /// `updated_client_order_id`
+    pub fn new(
+        order: &Order,
+        price: Price,
+        quantity: Contracts,
+        updated_client_order_id: impl Into<String>,
+    ) -> Result<Self> {
+        let cents = whole_cents(price)?;
+        Ok(Self {
+            ticker: order.leg.instrument.clone(),
+            side: order.leg.side,
+            action: order.action,
+            client_order_id: order.client_order_id.clone(),
+            updated_client_order_id: updated_client_order_id.into(),
+            yes_price: (order.leg.side == Side::Yes).then_some(cents),
+            no_price: (order.leg.side == Side::No).then_some(cents),
+            count: quantity.get(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KalshiOrderStatus {
+    Pending,
+    Resting,
+    Canceled,
+    Executed,
+}
+
+/// An order as the exchange reports it; prices, costs and fees in cents
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct KalshiOrder {
+    pub order_id: String,
+    #[serde(default)]
+    pub client_order_id: String,
+    pub ticker: String,
+    #[serde(with = "wire::side")]
+    pub side: Side,
+    #[serde(with = "wire::action")]
+    pub action: OrderAction,
+    pub status: KalshiOrderStatus,
+    #[serde(default)]
+    pub yes_price: u32,
+    #[serde(default)]
+    pub no_price: u32,
+    #[serde(default)]
+    pub fill_count: u64,
+    #[serde(default)]
+    pub remaining_count: u64,
+    #[serde(default)]
+    pub taker_fill_cost: i64,
+    #[serde(default)]
+    pub maker_fill_cost: i64,
+    #[serde(default)]
+    pub taker_fees: i64,
+    #[serde(default)]
+    pub maker_fees: i64,
+    #[serde(default)]
+    pub created_time: Option<DateTime<Utc>>,
+}
+
+impl KalshiOrder {
+    /// Paid (or received) for every fill so far, before fees
+    pub fn fill_cost(&self) -> Notional {
+        Notional::from_cents(self.taker_fill_cost + self.maker_fill_cost)
+    }
+
+    pub fn fees(&self) -> Notional {
+        Notional::from_cents(self.taker_fees + self.maker_fees)
+    }
+
+    /// Events taking `order`, as last seen locally, to this state
+    pub fn events_since(&self, order: &Order) -> Vec<OrderEvent> {
+        let mut events = Vec::new();
+        if order.venue_order_id.is_none() {
+            events.push(OrderEvent::Acked {
+                venue_order_id: self.order_id.clone(),
+            });
+        }
+        let new = self.fill_count.saturating_sub(order.filled.get());
+        if new > 0 {
+            // Fills since `order` was last updated, at their average price
+            let cost = self.fill_cost().saturating_sub(order.cost);
+            let units = (cost.0 / new as i64).clamp(0, Price::ONE.units() as i64);
+            events.push(OrderEvent::Fill {
+                quantity: Contracts(new),
+                price: Price(units as u32),
+                fee: self.fees().saturating_sub(order.fee),
+            });
+        }
+        if self.status == KalshiOrderStatus::Canceled && self.fill_count < order.quantity.get() {
+            events.push(OrderEvent::Cancelled);
+        }
+        events
+    }
+}
+
+/// One execution against one of our orders; prices in cents
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct KalshiFill {
+    pub trade_id: String,
+    pub order_id: String,
+    pub ticker: String,
+    #[serde(with = "wire::side")]
+    pub side: Side,
+    #[serde(with = "wire::action")]
+    pub action: OrderAction,
+    pub count: u64,
+    pub yes_price: u32,
+    pub no_price: u32,
+    #[serde(default)]
+    pub is_taker: bool,
+    #[serde(default)]
+    pub created_time: Option<DateTime<Utc>>,
+}
+
+/// Holdings in one market; amounts in cents
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct MarketPosition {
+    pub ticker: String,
+    /// Contracts held: positive for YES, negative for NO
+    pub position: i64,
+    #[serde(default)]
+    pub market_exposure: i64,
+    #[serde(default)]
+    pub realized_pnl: i64,
+    #[serde(default)]
+    pub fees_paid: i64,
+    #[serde(default)]
+    pub resting_orders_count: i64,
+}
+
+/// One page of a list endpoint
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    #[serde(alias = "orders", alias = "fills", alias = "market_positions")]
+    items: Vec<T>,
+    #[serde(default)]
+    cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderResponse {
+    order: KalshiOrder,
+}
+
+#[derive(Debug, Deserialize)]
+struct BalanceResponse {
+    /// Available balance in cents
+    balance: i64,
+}
+
+/// Signed client for Kalshi's portfolio endpoints
+#[derive(Debug)]
+pub struct KalshiRestClient {
+    http: reqwest::Client,
+    /// REST root, e.g. `https://demo-api.kalshi.co/trade-api/v2`
+    base_url: String,
+    signer: KalshiSigner,
+}
+
+impl KalshiRestClient {
+    pub fn new(base_url: impl Into<String>, signer: KalshiSigner) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            signer,
+        }
+    }
+
+    /// Send a signed request to `path` (relative to the base URL) and decode
+    /// the JSON response
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&(impl Serialize + Sync)>,
+    ) -> Result<T> {
+        let url = reqwest::Url::parse(&format!("{}{}", self.base_url, path))
+            .with_context(|| format!("Invalid Kalshi URL {}{}", self.base_url, path))?;
+        let timestamp = Utc::now().timestamp_millis();
+        let signature = self.signer.sign(timestamp, &method, url.path());
+
+        let mut request = self
+            .http
+            .request(method, url)
+            .header("KALSHI-ACCESS-KEY", self.signer.key_id())
+            .header("KALSHI-ACCESS-TIMESTAMP", timestamp.to_string())
+            .header("KALSHI-ACCESS-SIGNATURE", signature)
+            .query(query);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            let detail = serde_json::from_str::<ErrorBody>(&text)
+                .unwrap_or_default()
+                .error;
+            return Err(ApiError {
+                status,
+                code: detail.code,
+                message: if detail.message.is_empty() {
+                    text
+                } else {
+                    detail.message
+                },
+            }
+            .into());
+        }
+        response.json().await.context("Malformed Kalshi response")
+    }
+
+    /// Every item of a paginated list endpoint
+    async fn list<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        mut query: Vec<(&str, String)>,
+    ) -> Result<Vec<T>> {
+        query.push(("limit", PAGE_LIMIT.to_string()));
+        let mut items = Vec::new();
+        loop {
+            let page: Page<T> = self.request(Method::GET, path, &query, None::<&()>).await?;
+            items.extend(page.items);
+            match page.cursor.filter(|c| !c.is_empty()) {
+                Some(cursor) => {
+                    query.retain(|(name, _)| *name != "cursor");
+                    query.push(("cursor", cursor));
+                }
+                None => return Ok(items),
+            }
+        }
+    }
+
+    pub async fn create_order(&self, request: &CreateOrderRequest) -> Result<KalshiOrder> {
+        let response: OrderResponse = self
+            .request(Method::POST, "/portfolio/orders", &[], Some(request))
+            .await?;
+        Ok(response.order)
+    }
+
+    /// Cancel what is left of a resting order
+    pub async fn cancel_order(&self, order_id: &str) -> Result<KalshiOrder> {
+        let path = format!("/portfolio/orders/{}", order_id);
+        let response: OrderResponse = self
+            .request(Method::DELETE, &path, &[], None::<&()>)
+            .await?;
+        Ok(response.order)
+    }
+
+    /// Change a resting order's price or size; returns the amended order
+    pub async fn amend_order(
+        &self,
+        order_id: &str,
+        request: &AmendOrderRequest,
+    ) -> Result<KalshiOrder> {
+        let path = format!("/portfolio/orders/{}/amend", order_id);
+        let response: OrderResponse = self
+            .request(Method::POST, &path, &[], Some(request))
+            .await?;
+        Ok(response.order)
+    }
+
+    pub async fn get_order(&self, order_id: &str) -> Result<KalshiOrder> {
+        let path = format!("/portfolio/orders/{}", order_id);
+        let response: OrderResponse = self.request(Method::GET, &path, &[], None::<&()>).await?;
+        Ok(response.order)
+    }
+
+    /// Our orders, optionally only in `ticker`
+    pub async fn get_orders(&self, ticker: Option<&str>) -> Result<Vec<KalshiOrder>> {
+        let query = ticker
+            .map(|t| ("ticker", t.to_string()))
+            .into_iter()
+            .collect();
+        self.list("/portfolio/orders", query).await
+    }
+
+    /// Our fills, optionally only in `ticker` or for `order_id`
+    pub async fn get_fills(
+        &self,
+        ticker: Option<&str>,
+        order_id: Option<&str>,
+    ) -> Result<Vec<KalshiFill>> {
+        let query = [("ticker", ticker), ("order_id", order_id)]
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, value?.to_string())))
+            .collect();
+        self.list("/portfolio/fills", query).await
+    }
+
+    /// Open positions by market
+    pub async fn get_positions(&self) -> Result<Vec<MarketPosition>> {
+        self.list("/portfolio/positions", Vec::new()).await
+    }
+
+    /// Available balance
+    pub async fn get_balance(&self) -> Result<Notional> {
+        let response: BalanceResponse = self
+            .request(Method::GET, "/portfolio/balance", &[], None::<&()>)
+            .await?;
+        Ok(Notional::from_cents(response.balance))
+    }
+}
+
+/// Whether `e` is the exchange refusing the request (rather than the outcome
+/// being unknown)
+fn is_refusal(e: &anyhow::Error) -> Option<&ApiError> {
+    e.downcast_ref::<ApiError>()
+        .filter(|api| api.status.is_client_error())
+}
+
+impl OrderApi for KalshiRestClient {
+    fn venue(&self) -> Venue {
+        Venue::Kalshi
+    }
+
+    fn latency(&self) -> Duration {
+        TYPICAL_LATENCY
+    }
+
+    fn place<'a>(&'a self, order: &'a Order) -> BoxFuture<'a, Result<Vec<OrderEvent>>> {
+        Box::pin(async move {
+            let request = match CreateOrderRequest::limit(order) {
+                Ok(request) => request,
+                Err(e) => {
+                    return Ok(vec![OrderEvent::Rejected {
+                        reason: e.to_string(),
+                    }])
+                }
+            };
+            match self.create_order(&request).await {
+                Ok(placed) => Ok(placed.events_since(order)),
+                Err(e) => match is_refusal(&e) {
+                    Some(api) => Ok(vec![OrderEvent::Rejected {
+                        reason: api.to_string(),
+                    }]),
+                    None => Err(e),
+                },
+            }
+        })
+    }
+
+    fn cancel<'a>(&'a self, order: &'a Order) -> BoxFuture<'a, Result<Vec<OrderEvent>>> {
+        Box::pin(async move {
+            // Never acknowledged: look it up by our id in case it did arrive
+            let venue_order_id = match &order.venue_order_id {
+                Some(id) => id.clone(),
+                None => {
+                    let orders = self.get_orders(Some(&order.leg.instrument)).await?;
+                    match orders
+                        .into_iter()
+                        .find(|o| o.client_order_id == order.client_order_id)
+                    {
+                        Some(found) if found.status == KalshiOrderStatus::Resting => found.order_id,
+                        Some(found) => return Ok(found.events_since(order)),
+                        None => return Ok(vec![OrderEvent::Cancelled]),
+                    }
+                }
+            };
+            match self.cancel_order(&venue_order_id).await {
+                Ok(cancelled) => Ok(cancelled.events_since(order)),
+                // Already done: report how it ended instead
+                Err(e) if is_refusal(&e).is_some() => {
+                    Ok(self.get_order(&venue_order_id).await?.events_since(order))
+                }
+                Err(e) => Err(e),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orders::OrderManager;
+    use crate::types::Leg;
+    use rsa::pss::VerifyingKey;
+    use rsa::signature::Verifier;
+    use std::sync::OnceLock;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn test_key() -> RsaPrivateKey {
+        static KEY: OnceLock<RsaPrivateKey> = OnceLock::new();
+        KEY.get_or_init(|| RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap())
+            .clone()
+    }
+
+    fn verify(signature: &str, message: &str) -> bool {
+        let key = VerifyingKey::<Sha256>::new(test_key().to_public_key());
+        let bytes = BASE64.decode(signature).unwrap();
+        let signature = rsa::pss::Signature::try_from(bytes.as_slice()).unwrap();
+        key.verify(message.as_bytes(), &signature).is_ok()
+    }
+
+    /// Local mock exchange answering each request with the next response;
+    /// returns its base URL and the raw requests it received
+    async fn mock_server(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}/trade-api/v2", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            let (mut socket, _) = listener.accept().await.unwrap();
+            for (status, body) in responses {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = header(&text, "content-length").parse().unwrap_or(0);
+                        if request.len() >= end + 4 + length {
+                            break;
+                        }
+                    }
+                }
+                requests.push(String::from_utf8_lossy(&request).into_owned());
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-length: {}\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+        (base_url, server)
+    }
+
+    /// Value of header `name` in a raw request, or "" if absent
+    fn header<'a>(request: &'a str, name: &str) -> &'a str {
+        let prefix = format!("{}: ", name.to_lowercase());
+        request
+            .lines()
+            .find(|l| l.to_lowercase().starts_with(&prefix))
+            .map_or("", |l| l[prefix.len()..].trim())
+    }
+
+    #[test]
+    fn test_signature_covers_timestamp_method_and_path() {
+        let signer = KalshiSigner::new("key-1", test_key());
+        let path = "/trade-api/v2/portfolio/balance";
+        let signature = signer.sign(1_700_000_000_000, &Method::GET, path);
+        assert!(verify(&signature, &format!("1700000000000GET{}", path)));
+        assert!(!verify(&signature, &format!("1700000000000POST{}", path)));
+    }
+
+    #[tokio::test]
+    async fn test_orders_against_mock_exchange() {
+        let partial = r#"{"order": {"order_id": "k-1", "client_order_id": "arb-1-1",
+            "ticker": "KXTEST-YES", "side": "yes", "action": "buy", "status": "canceled",
+            "yes_price": 40, "no_price": 60, "fill_count": 4, "remaining_count": 0,
+            "taker_fill_cost": 160, "taker_fees": 7}}"#;
+        let refused = r#"{"error": {"code": "insufficient_balance",
+            "message": "Insufficient balance"}}"#;
+        let (base_url, server) = mock_server(vec![(201, partial), (400, refused)]).await;
+        let client = KalshiRestClient::new(base_url, KalshiSigner::new("key-1", test_key()));
+
+        let orders = OrderManager::new();
+        let leg = Leg {
+            venue: Venue::Kalshi,
+            instrument: "KXTEST-YES".into(),
+            side: Side::Yes,
+            outcome: Side::Yes,
+        };
+        let (price, tif) = (Price::from_cents(40), TimeInForce::ImmediateOrCancel);
+        let order = orders.create("m", leg, OrderAction::Buy, price, Contracts(10), tif);
+        let events = client.place(&order).await.unwrap();
+        let fill = OrderEvent::Fill {
+            quantity: Contracts(4),
+            price: Price::from_cents(40),
+            fee: Notional::from_cents(7),
+        };
+        let acked = OrderEvent::Acked {
+            venue_order_id: "k-1".into(),
+        };
+        assert_eq!(events, vec![acked, fill, OrderEvent::Cancelled]);
+
+        // A refused order is a rejection, not an unknown outcome
+        let events = client.place(&order).await.unwrap();
+        let [OrderEvent::Rejected { reason }] = &events[..] else {
+            panic!("expected a rejection, got {:?}", events);
+        };
+        assert!(reason.contains("Insufficient balance"));
+
+        let requests = server.await.unwrap();
+        let request = &requests[0];
+        assert!(request.starts_with("POST /trade-api/v2/portfolio/orders HTTP/1.1"));
+        assert!(request.contains(r#""time_in_force":"immediate_or_cancel""#));
+        assert!(request.contains(r#""yes_price":40"#));
+        assert_eq!(header(request, "KALSHI-ACCESS-KEY"), "key-1");
+        let timestamp = header(request, "KALSHI-ACCESS-TIMESTAMP");
+        let message = format!("{}POST/trade-api/v2/portfolio/orders", timestamp);
+        assert!(verify(header(request, "KALSHI-ACCESS-SIGNATURE"), &message));
+    }
+}
@@ -1,6 +1,21 @@
+pub mod basket;
 pub mod config;
+pub mod edge;
+pub mod engine;
 pub mod execution;
+pub mod feed;
+pub mod fees;
 pub mod kalshi;
+pub mod kalshi_messages;
+pub mod kalshi_rest;
+pub mod lifecycle;
+pub mod lines;
+pub mod orderbook;
+pub mod orders;
 pub mod polymarket;
+pub mod polymarket_messages;
 pub mod position_tracker;
+pub mod registry;
+pub mod supervisor;
 pub mod types;
+pub mod unwind;
@@ -0,0 +1,343 @@
+//! Opportunity lifecycle tracking.
+//!
+//! Detection re-finds a standing opportunity on every book update and sweep.
+//! [`OpportunityLifecycle`] recognises it as the same opportunity (same
+//! market or event, same strategy, same price levels), records when it
+//! opened, how its edge moved and how long it lasted, and decides when
+//! execution should see it: once, when it is first handed over, then again
+//! only if its edge improves. A price move is a new opportunity.
+//! Each change is emitted as an [`OpportunityEvent`].
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+
+use crate::lines::MiddleKind;
+use crate::types::{ArbType, Notional, Opportunity, Price, Side};
+
+/// Which strategy an opportunity is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpportunityKind {
+    Pair(ArbType),
+    Basket(Side),
+    Middle(ArbType, MiddleKind),
+}
+
+/// Identity of an opportunity across detection passes
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OpportunityKey {
+    /// Market id, or event id for baskets
+    pub scope: String,
+    pub kind: OpportunityKind,
+    /// Top-of-book price of every leg
+    pub levels: Vec<Price>,
+}
+
+impl std::fmt::Display for OpportunityKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            OpportunityKind::Pair(arb_type) => write!(f, "{} [{}]", self.scope, arb_type)?,
+            OpportunityKind::Basket(side) => write!(f, "{} [{} basket]", self.scope, side)?,
+            OpportunityKind::Middle(arb_type, kind) => {
+                write!(f, "{} [{} {}]", self.scope, kind, arb_type)?
+            }
+        }
+        let levels: Vec<String> = self.levels.iter().map(Price::to_string).collect();
+        write!(f, " @ {}", levels.join("/"))
+    }
+}
+
+impl Opportunity {
+    pub fn key(&self) -> OpportunityKey {
+        let (scope, kind) = match self {
+            Opportunity::Pair(arb) => (&arb.market_id, OpportunityKind::Pair(arb.arb_type)),
+            Opportunity::Basket(b) => (&b.event_id, OpportunityKind::Basket(b.side)),
+            Opportunity::Middle(m) => (&m.market_id, OpportunityKind::Middle(m.arb_type, m.kind)),
+        };
+        OpportunityKey {
+            scope: scope.clone(),
+            kind,
+            levels: self.levels(),
+        }
+    }
+
+    /// Profit per contract (or basket) at the top of the book.
+    /// For a middle, the worst case over its scenarios.
+    pub fn edge(&self) -> Notional {
+        match self {
+            Opportunity::Pair(arb) => Notional(arb.profit.units() as i64),
+            Opportunity::Basket(b) => {
+                Notional(b.expected_profit.0 / b.quantity.get().max(1) as i64)
+            }
+            Opportunity::Middle(m) => m
+                .scenarios
+                .iter()
+                .map(|s| s.profit)
+                .min()
+                .unwrap_or(Notional::ZERO),
+        }
+    }
+
+    /// Top-of-book price of every leg
+    pub fn levels(&self) -> Vec<Price> {
+        match self {
+            Opportunity::Pair(arb) => vec![arb.yes_price, arb.no_price],
+            Opportunity::Basket(b) => b.legs.iter().map(|l| l.price).collect(),
+            Opportunity::Middle(m) => vec![m.yes_price, m.no_price],
+        }
+    }
+}
+
+/// A standing opportunity
+#[derive(Debug, Clone)]
+pub struct LiveOpportunity {
+    pub first_seen: Instant,
+    pub last_seen: Instant,
+    pub edge: Notional,
+    pub peak_edge: Notional,
+    /// Times the edge changed while open
+    pub updates: u32,
+    /// Edge when execution last saw it, if it has
+    handed_edge: Option<Notional>,
+}
+
+impl LiveOpportunity {
+    /// How long it has been open
+    pub fn duration(&self) -> Duration {
+        self.last_seen.duration_since(self.first_seen)
+    }
+}
+
+/// What a detection pass meant for one opportunity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Opened,
+    /// The edge moved; `improved` if it beats what execution saw
+    Updated {
+        improved: bool,
+    },
+    Unchanged,
+}
+
+/// A change in an opportunity's lifecycle
+#[derive(Debug, Clone)]
+pub enum OpportunityEvent {
+    Opened {
+        key: OpportunityKey,
+        edge: Notional,
+    },
+    Updated {
+        key: OpportunityKey,
+        edge: Notional,
+        improved: bool,
+    },
+    Closed {
+        key: OpportunityKey,
+        opportunity: LiveOpportunity,
+    },
+}
+
+impl OpportunityEvent {
+    /// The event for `transition` of `key`, if anything changed
+    pub fn of(key: &OpportunityKey, transition: Transition, edge: Notional) -> Option<Self> {
+        let key = key.clone();
+        match transition {
+            Transition::Opened => Some(OpportunityEvent::Opened { key, edge }),
+            Transition::Updated { improved } => Some(OpportunityEvent::Updated {
+                key,
+                edge,
+                improved,
+            }),
+            Transition::Unchanged => None,
+        }
+    }
+}
+
+/// Log every lifecycle event until the detection side hangs up
+pub async fn log_opportunity_events(mut events: mpsc::UnboundedReceiver<OpportunityEvent>) {
+    while let Some(event) = events.recv().await {
+        match event {
+            OpportunityEvent::Opened { key, edge } => {
+                info!("[ARB] Opened {} | edge {}", key, edge)
+            }
+            OpportunityEvent::Updated {
+                key,
+                edge,
+                improved,
+            } => debug!(
+                "[ARB] Updated {} | edge {}{}",
+                key,
+                edge,
+                if improved { " (improved)" } else { "" }
+            ),
+            OpportunityEvent::Closed { key, opportunity } => info!(
+                "[ARB] Closed {} after {:.1}s | peak edge {} | {} updates",
+                key,
+                opportunity.duration().as_secs_f64(),
+                opportunity.peak_edge,
+                opportunity.updates
+            ),
+        }
+    }
+}
+
+/// Every open opportunity, by key
+#[derive(Debug, Default)]
+pub struct OpportunityLifecycle {
+    live: HashMap<OpportunityKey, LiveOpportunity>,
+}
+
+impl OpportunityLifecycle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `opportunity` was found at `now`
+    pub fn observe(&mut self, opportunity: &Opportunity, now: Instant) -> Transition {
+        let edge = opportunity.edge();
+        let Some(live) = self.live.get_mut(&opportunity.key()) else {
+            self.live.insert(
+                opportunity.key(),
+                LiveOpportunity {
+                    first_seen: now,
+                    last_seen: now,
+                    edge,
+                    peak_edge: edge,
+                    updates: 0,
+                    handed_edge: None,
+                },
+            );
+            return Transition::Opened;
+        };
+
+        live.last_seen = now;
+        if live.edge == edge {
+            return Transition::Unchanged;
+        }
+        live.edge = edge;
+        live.peak_edge = live.peak_edge.max(edge);
+        live.updates += 1;
+        Transition::Updated {
+            improved: live.handed_edge.is_some_and(|handed| edge > handed),
+        }
+    }
+
+    /// Whether execution should see `key` (again): it never has, or the
+    /// edge improved since
+    pub fn should_hand(&self, key: &OpportunityKey) -> bool {
+        self.live
+            .get(key)
+            .is_some_and(|live| live.handed_edge.is_none_or(|handed| live.edge > handed))
+    }
+
+    /// Record that `key` was handed to execution at its current edge
+    pub fn mark_handed(&mut self, key: &OpportunityKey) {
+        if let Some(live) = self.live.get_mut(key) {
+            live.handed_edge = Some(live.edge);
+        }
+    }
+
+    /// Close every open opportunity in `scope` that is not in `found`
+    pub fn close_missing(
+        &mut self,
+        scope: &str,
+        found: &[OpportunityKey],
+    ) -> Vec<(OpportunityKey, LiveOpportunity)> {
+        let closed: Vec<OpportunityKey> = self
+            .live
+            .keys()
+            .filter(|key| key.scope == scope && !found.contains(key))
+            .cloned()
+            .collect();
+        closed
+            .into_iter()
+            .filter_map(|key| self.live.remove_entry(&key))
+            .collect()
+    }
+
+    /// Number of opportunities currently open
+    pub fn open_count(&self) -> usize {
+        self.live.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::EdgeReturn;
+    use crate::fees::PairFees;
+    use crate::types::{ArbOpportunity, Contracts, Venue};
+
+    fn arb(yes: u32, no: u32) -> Opportunity {
+        let pair = crate::config::get_hardcoded_markets().remove(0);
+        let (yes_price, no_price) = (Price::from_cents(yes), Price::from_cents(no));
+        let total_cost = yes_price + no_price;
+        Opportunity::Pair(ArbOpportunity {
+            market_id: pair.id.clone(),
+            description: pair.description.clone(),
+            arb_type: ArbType::PolyOnly,
+            yes_price,
+            no_price,
+            total_cost,
+            fee: Price::ZERO,
+            profit: Price::ONE.saturating_sub(total_cost),
+            quantity: Contracts(10),
+            yes_vwap: yes as f64,
+            no_vwap: no as f64,
+            yes_limit: yes_price,
+            no_limit: no_price,
+            yes_tick: Price::CENT,
+            no_tick: Price::CENT,
+            yes_size: Contracts(10),
+            no_size: Contracts(10),
+            expected_profit: Notional::ZERO,
+            edge_return: EdgeReturn::new(Notional::ZERO, total_cost.into(), None),
+            fees: PairFees::default(),
+            yes_leg: pair.leg(Venue::Polymarket, Side::Yes),
+            no_leg: pair.leg(Venue::Polymarket, Side::No),
+            timestamp: chrono::Utc::now(),
+        })
+    }
+
+    #[test]
+    fn test_standing_opportunity_is_handed_over_once() {
+        let mut lifecycle = OpportunityLifecycle::new();
+        let start = Instant::now();
+        let first = arb(40, 55);
+        let key = first.key();
+
+        assert_eq!(lifecycle.observe(&first, start), Transition::Opened);
+        // Not handed over yet (e.g. the position limit held it back)
+        assert!(lifecycle.should_hand(&key));
+        lifecycle.mark_handed(&key);
+        // Re-detected on every update: same prices, nothing new for execution
+        for i in 1..=5 {
+            let now = start + Duration::from_millis(500 * i);
+            assert_eq!(lifecycle.observe(&arb(40, 55), now), Transition::Unchanged);
+            assert!(!lifecycle.should_hand(&key));
+        }
+        // A thinner edge at the same prices updates the record only
+        let mut thinner = arb(40, 55);
+        if let Opportunity::Pair(arb) = &mut thinner {
+            arb.profit = Price::from_cents(4);
+        }
+        let updated = lifecycle.observe(&thinner, start + Duration::from_secs(3));
+        assert_eq!(updated, Transition::Updated { improved: false });
+        assert!(!lifecycle.should_hand(&key));
+
+        // New prices are a new opportunity
+        let better = arb(39, 55);
+        assert_ne!(better.key(), key);
+        let now = start + Duration::from_secs(4);
+        assert_eq!(lifecycle.observe(&better, now), Transition::Opened);
+
+        let closed = lifecycle.close_missing(&key.scope, &[better.key()]);
+        let (closed_key, live) = &closed[0];
+        assert_eq!(closed_key, &key);
+        assert_eq!(live.duration(), Duration::from_secs(3));
+        assert_eq!(live.peak_edge, Notional::from_cents(5));
+        assert_eq!(live.updates, 1);
+        assert_eq!(lifecycle.open_count(), 1);
+    }
+}
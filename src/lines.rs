@@ -0,0 +1,216 @@
+//! Spread and total lines, and "middle" detection across mismatched lines.
+//!
+//! A line market's YES pays when the game's value (total points, or the
+//! named team's winning margin) ends above the line. When Kalshi and
+//! Polymarket list the same game at different lines, YES on one venue plus
+//! NO on the other is not an arbitrage: with the YES line below the NO
+//! line both legs pay in between (a middle); the other way round neither
+//! does (a gap). Pairs on adjacent lines are reported with every outcome
+//! spelled out instead of as risk-free arbs.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+use tracing::debug;
+
+use crate::execution::{check_freshness, DetectionStats, FreshnessLimits, Staleness};
+use crate::fees::FeeModel;
+use crate::types::{ArbType, MarketState, MiddleOpportunity, Notional, Price, Side, Venue};
+
+/// Spread or total line in tenths of a point (220.5 = `Line(2205)`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Line(pub i32);
+
+impl Line {
+    /// Line from points, rounded to the nearest tenth
+    pub fn from_points(points: f64) -> Self {
+        Line((points * 10.0).round() as i32)
+    }
+}
+
+impl std::fmt::Display for Line {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let tenths = self.0.unsigned_abs();
+        if tenths.is_multiple_of(10) {
+            write!(f, "{}{}", sign, tenths / 10)
+        } else {
+            write!(f, "{}{}.{}", sign, tenths / 10, tenths % 10)
+        }
+    }
+}
+
+/// Widest distance between two lines still reported as a middle or gap
+pub const MAX_MIDDLE_WIDTH: Line = Line(30);
+
+/// Which way round a cross-venue pair on different lines is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MiddleKind {
+    /// YES line below the NO line: both legs pay between the lines
+    Middle,
+    /// YES line above the NO line: neither leg pays between the lines
+    Gap,
+}
+
+impl MiddleKind {
+    pub fn of(yes_line: Line, no_line: Line) -> Self {
+        if yes_line < no_line {
+            MiddleKind::Middle
+        } else {
+            MiddleKind::Gap
+        }
+    }
+}
+
+impl std::fmt::Display for MiddleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MiddleKind::Middle => write!(f, "MIDDLE"),
+            MiddleKind::Gap => write!(f, "GAP"),
+        }
+    }
+}
+
+/// One range of final values and what a YES + NO pair pays in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scenario {
+    /// Final value range, e.g. "220.5 < value <= 221.5"
+    pub outcome: String,
+    /// Payout per pair
+    pub payout: Price,
+    /// Payout minus cost, per pair
+    pub profit: Notional,
+}
+
+/// Every outcome of buying YES above `yes_line` plus NO at-or-below
+/// `no_line` for `cost` per pair
+pub fn scenarios(yes_line: Line, no_line: Line, cost: Price) -> Vec<Scenario> {
+    let scenario = |outcome: String, winners: u32| {
+        let payout = Price(winners * Price::SCALE);
+        Scenario {
+            outcome,
+            payout,
+            profit: Notional(payout.units() as i64 - cost.units() as i64),
+        }
+    };
+    let (low, high) = (yes_line.min(no_line), yes_line.max(no_line));
+    let mut all = vec![scenario(format!("value <= {}", low), 1)];
+    if low != high {
+        // YES below NO: both legs pay in between; otherwise neither does
+        let winners = if yes_line < no_line { 2 } else { 0 };
+        all.push(scenario(format!("{} < value <= {}", low, high), winners));
+    }
+    all.push(scenario(format!("value > {}", high), 1));
+    all
+}
+
+/// Check the cross-venue pairs of a market whose venues list different but
+/// adjacent lines (at most [`MAX_MIDDLE_WIDTH`] apart). Each quoted pair is
+/// reported, labelled a middle or a gap, whatever it costs: the scenarios
+/// say whether it is worth taking. `Err` if stale or skewed legs left
+/// nothing to report.
+pub fn detect_middle(
+    market: &MarketState,
+    limits: &FreshnessLimits,
+    stats: &DetectionStats,
+) -> Result<Vec<MiddleOpportunity>, Staleness> {
+    let pair = &market.pair;
+    let (kalshi_line, poly_line) = match (pair.kalshi_line, pair.poly_line) {
+        (Some(k), Some(p)) if k != p && (k.0 - p.0).abs() <= MAX_MIDDLE_WIDTH.0 => (k, p),
+        _ => return Ok(Vec::new()),
+    };
+    let now = Instant::now();
+
+    let mut found = Vec::new();
+    let mut skipped = None;
+    for arb_type in [ArbType::PolyYesKalshiNo, ArbType::KalshiYesPolyNo] {
+        let (yes_venue, no_venue) = arb_type.venues();
+        let yes_book = market.book(yes_venue).load();
+        let no_book = market.book(no_venue).load();
+        let yes_top = yes_book.asks(Side::Yes).first();
+        let (Some(yes), Some(no)) = (yes_top, no_book.asks(Side::No).first()) else {
+            continue;
+        };
+
+        let fee = pair.fees.venue(yes_venue).unit_fee(yes.price)
+            + pair.fees.venue(no_venue).unit_fee(no.price);
+        let total_cost = yes.price + no.price + fee;
+        if let Err(reason) = check_freshness(
+            limits.confirm(yes_venue, yes_book.stamp(Side::Yes)),
+            limits.confirm(no_venue, no_book.stamp(Side::No)),
+            limits,
+            now,
+        ) {
+            let counter = match reason {
+                Staleness::Stale => &stats.stale_legs,
+                Staleness::Skewed => &stats.skewed_legs,
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+            debug!(
+                "[ARB] Skipping middle {} on {}: legs {:?}",
+                arb_type, pair.description, reason
+            );
+            skipped = Some(reason);
+            continue;
+        }
+
+        let line = |venue| match venue {
+            Venue::Kalshi => kalshi_line,
+            Venue::Polymarket => poly_line,
+        };
+        let middle = MiddleOpportunity {
+            market_id: pair.id.clone(),
+            description: pair.description.clone(),
+            arb_type,
+            kind: MiddleKind::of(line(yes_venue), line(no_venue)),
+            yes_leg: pair.leg(yes_venue, Side::Yes),
+            no_leg: pair.leg(no_venue, Side::No),
+            yes_line: line(yes_venue),
+            no_line: line(no_venue),
+            yes_price: yes.price,
+            no_price: no.price,
+            fee,
+            total_cost,
+            quantity: yes.size.min(no.size),
+            scenarios: scenarios(line(yes_venue), line(no_venue), total_cost),
+            timestamp: chrono::Utc::now(),
+        };
+        found.push(middle);
+    }
+    match skipped {
+        Some(reason) if found.is_empty() => Err(reason),
+        _ => Ok(found),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scenarios_cover_every_final_value() {
+        let cost = Price::from_cents(97);
+        let (lower, upper) = (Line::from_points(220.5), Line::from_points(221.5));
+
+        // Over 220.5 + under 221.5: one leg always pays, both on 221
+        let middle = scenarios(lower, upper, cost);
+        let payouts: Vec<Price> = middle.iter().map(|s| s.payout).collect();
+        assert_eq!(
+            payouts,
+            vec![Price::ONE, Price(2 * Price::SCALE), Price::ONE]
+        );
+        assert_eq!(middle[1].outcome, "220.5 < value <= 221.5");
+        assert_eq!(middle[1].profit, Notional::from_cents(103));
+
+        // Over 221.5 + under 220.5: both legs lose on 221
+        let gap = scenarios(upper, lower, cost);
+        assert_eq!(gap[1].payout, Price::ZERO);
+        assert_eq!(gap[1].profit, Notional::from_cents(-97));
+
+        // Same line: exactly one leg pays, whatever happens
+        assert_eq!(scenarios(lower, lower, cost).len(), 2);
+        assert_eq!(MiddleKind::of(lower, upper), MiddleKind::Middle);
+        assert_eq!(MiddleKind::of(upper, lower), MiddleKind::Gap);
+        assert_eq!(Line::from_points(-3.5).to_string(), "-3.5");
+    }
+}
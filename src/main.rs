@@ -3,29 +3,32 @@
 //! A simplified version of the production arbitrage bot, focused on:
 //! - WebSocket price feeds (Kalshi + Polymarket)
 //! - Basic arbitrage detection (YES + NO < $1.00)
-//! - Dry-run execution (no actual trading)
+//! - Dry-run execution, or live two-leg execution once both venues have an
+//!   order client
 //! - Position tracking and P&L calculation
 
-use anyhow::Result;
-use std::collections::HashMap;
+use anyhow::{Context, Result};
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::{error, info, warn};
 
-mod config;
-mod execution;
-mod kalshi;
-mod polymarket;
-mod position_tracker;
-mod types;
-
-use config::{
-    get_hardcoded_markets, is_dry_run, max_daily_loss_cents, max_position_size,
-    WS_RECONNECT_DELAY_SECS,
+use prediction_market_arbitrage_mvp::config::{
+    endpoints, environment, get_hardcoded_events, get_hardcoded_markets, is_dry_run,
+    kalshi_credentials, max_daily_loss_cents, max_position_size, order_timeout_ms,
+};
+use prediction_market_arbitrage_mvp::engine::{ExecutionEngine, OrderApi};
+use prediction_market_arbitrage_mvp::execution::{
+    check_arbitrage_opportunities, execute_arbitrage_loop, DetectionStats,
 };
-use execution::{check_arbitrage_opportunities, execute_arbitrage_loop};
-use position_tracker::PositionTracker;
-use types::MarketState;
+use prediction_market_arbitrage_mvp::kalshi::KalshiFeed;
+use prediction_market_arbitrage_mvp::kalshi_rest::{KalshiRestClient, KalshiSigner};
+use prediction_market_arbitrage_mvp::lifecycle::log_opportunity_events;
+use prediction_market_arbitrage_mvp::orders::OrderManager;
+use prediction_market_arbitrage_mvp::polymarket::PolymarketFeed;
+use prediction_market_arbitrage_mvp::position_tracker::PositionTracker;
+use prediction_market_arbitrage_mvp::registry::MarketRegistry;
+use prediction_market_arbitrage_mvp::supervisor::{supervise, ConnectionHealth};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -46,85 +49,161 @@ async fn main() -> Result<()> {
         if is_dry_run() {
             "DRY RUN (simulation only)"
         } else {
-            "LIVE (NOT IMPLEMENTED - will log only)"
+            "LIVE (orders are sent)"
         }
     );
-    info!("   Max position size: {} contracts", max_position_size());
+    let endpoints = endpoints()?;
+    info!("   Environment: {}", environment()?);
     info!(
-        "   Max daily loss: ${:.2}",
-        max_daily_loss_cents() as f64 / 100.0
+        "   Kalshi: {} | {}",
+        endpoints.kalshi_ws, endpoints.kalshi_rest
     );
+    info!(
+        "   Polymarket: {} | {}",
+        endpoints.poly_ws, endpoints.poly_rest
+    );
+    info!("   Max position size: {} contracts", max_position_size());
+    info!("   Max daily loss: {}", max_daily_loss_cents());
+
+    // Kalshi REST client, if credentials are configured, checked with a
+    // signed balance request
+    let kalshi_rest = match kalshi_credentials() {
+        Some((key_id, key_path)) => match KalshiSigner::from_pem_file(key_id, &key_path) {
+            Ok(signer) => Some(Arc::new(KalshiRestClient::new(
+                endpoints.kalshi_rest.clone(),
+                signer,
+            ))),
+            Err(e) => {
+                warn!("[KALSHI] {:#}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    if let Some(client) = &kalshi_rest {
+        match client.get_balance().await {
+            Ok(balance) => info!("   Kalshi balance: {}", balance),
+            Err(e) => warn!("[KALSHI] Balance check failed: {:#}", e),
+        }
+    }
 
     // Initialize market state
-    let markets = Arc::new(RwLock::new(HashMap::new()));
+    let registry = Arc::new(MarketRegistry::new());
     for pair in get_hardcoded_markets() {
-        let id = pair.id.clone();
-        let state = Arc::new(MarketState::new(pair));
-        markets.write().unwrap().insert(id, state);
+        registry.add(pair);
+    }
+    for group in get_hardcoded_events() {
+        registry.add_event(group);
     }
 
-    let market_count = markets.read().unwrap().len();
-    info!("   Tracked markets: {}", market_count);
+    info!("   Tracked markets: {}", registry.len());
+    info!("   Tracked events: {}", registry.events().len());
 
     // Initialize position tracker
     let position_tracker = Arc::new(RwLock::new(PositionTracker::new()));
 
+    // Live mode needs an order client on both venues; never start it with
+    // one missing
+    let engine = if is_dry_run() {
+        None
+    } else {
+        let kalshi: Arc<dyn OrderApi> = kalshi_rest.context(
+            "Live mode needs a Kalshi order client: set KALSHI_API_KEY_ID and KALSHI_PRIVATE_KEY_PATH",
+        )?;
+        // No Polymarket order client exists yet
+        let poly: Option<Arc<dyn OrderApi>> = None;
+        let poly = poly.context("Live mode needs a Polymarket order client: run with DRY_RUN=1")?;
+        let orders = Arc::new(OrderManager::new());
+        // Fills update positions as the venues report them
+        orders.subscribe(position_tracker.clone());
+        Some(ExecutionEngine::new(
+            kalshi,
+            poly,
+            orders,
+            Duration::from_millis(order_timeout_ms()),
+        ))
+    };
+
     // Create arbitrage channel
     let (arb_tx, arb_rx) = mpsc::unbounded_channel();
 
+    // Shared per-venue connection state
+    let health = Arc::new(ConnectionHealth::new());
+
+    // Book change notifications from the feeds to detection
+    let (update_tx, update_rx) = mpsc::unbounded_channel();
+
     // Spawn Kalshi WebSocket task
-    let kalshi_markets = markets.clone();
-    let kalshi_handle = tokio::spawn(async move {
-        loop {
-            if let Err(e) = kalshi::run_kalshi_ws(kalshi_markets.clone()).await {
-                error!("[KALSHI] WebSocket error: {} - reconnecting...", e);
-            }
-            tokio::time::sleep(tokio::time::Duration::from_secs(WS_RECONNECT_DELAY_SECS)).await;
-        }
-    });
+    let kalshi_client = KalshiFeed::new(endpoints.kalshi_ws.clone());
+    let kalshi_handle = tokio::spawn(supervise(
+        kalshi_client,
+        registry.clone(),
+        health.clone(),
+        update_tx.clone(),
+    ));
 
     // Spawn Polymarket WebSocket task
-    let poly_markets = markets.clone();
-    let poly_handle = tokio::spawn(async move {
-        loop {
-            if let Err(e) = polymarket::run_polymarket_ws(poly_markets.clone()).await {
-                error!("[POLYMARKET] WebSocket error: {} - reconnecting...", e);
-            }
-            tokio::time::sleep(tokio::time::Duration::from_secs(WS_RECONNECT_DELAY_SECS)).await;
-        }
-    });
+    let poly_client = PolymarketFeed::new(endpoints.poly_ws.clone());
+    let poly_handle = tokio::spawn(supervise(
+        poly_client,
+        registry.clone(),
+        health.clone(),
+        update_tx,
+    ));
 
     // Spawn arbitrage detection task
-    let arb_markets = markets.clone();
+    let detection_stats = Arc::new(DetectionStats::new());
+    let arb_registry = registry.clone();
     let arb_tracker = position_tracker.clone();
+    let arb_stats = detection_stats.clone();
+    let arb_health = health.clone();
+    // Opportunity open/update/close events from detection
+    let (event_tx, event_rx) = mpsc::unbounded_channel();
+    let event_handle = tokio::spawn(log_opportunity_events(event_rx));
     let arb_detection_handle = tokio::spawn(async move {
-        check_arbitrage_opportunities(arb_markets, arb_tracker, arb_tx).await;
+        check_arbitrage_opportunities(
+            arb_registry,
+            arb_tracker,
+            arb_tx,
+            arb_stats,
+            arb_health,
+            update_rx,
+            event_tx,
+        )
+        .await;
     });
 
     // Spawn execution task
     let exec_tracker = position_tracker.clone();
     let execution_handle = tokio::spawn(async move {
-        if let Err(e) = execute_arbitrage_loop(arb_rx, exec_tracker).await {
+        if let Err(e) = execute_arbitrage_loop(arb_rx, exec_tracker, engine).await {
             error!("[EXECUTION] Error: {}", e);
         }
     });
 
     // Spawn heartbeat/monitoring task
     let heartbeat_tracker = position_tracker.clone();
+    let heartbeat_health = health.clone();
+    let heartbeat_stats = detection_stats.clone();
     let heartbeat_handle = tokio::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
         loop {
             interval.tick().await;
             let tracker = heartbeat_tracker.read().unwrap();
-            info!("💓 System heartbeat | {}", tracker.summary());
+            info!(
+                "💓 System heartbeat | {} | {} | {}",
+                tracker.summary(),
+                heartbeat_health.summary(),
+                heartbeat_stats.summary()
+            );
 
             // Check circuit breaker
             let total_loss = -tracker.total_pnl();
-            if total_loss > max_daily_loss_cents() as i32 {
+            if total_loss > max_daily_loss_cents() {
                 warn!(
-                    "⚠️  CIRCUIT BREAKER TRIGGERED | Loss: ${:.2} exceeds limit ${:.2}",
-                    total_loss as f64 / 100.0,
-                    max_daily_loss_cents() as f64 / 100.0
+                    "⚠️  CIRCUIT BREAKER TRIGGERED | Loss: {} exceeds limit {}",
+                    total_loss,
+                    max_daily_loss_cents()
                 );
                 warn!("   System would halt in production mode");
             }
@@ -140,6 +219,7 @@ async fn main() -> Result<()> {
         poly_handle,
         arb_detection_handle,
         execution_handle,
+        event_handle,
         heartbeat_handle
     );
 
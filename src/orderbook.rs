@@ -0,0 +1,328 @@
+//! Price-level (L2) orderbook maintenance.
+//!
+//! Venue adapters keep one of these books per instrument, apply snapshots and
+//! incremental updates to it, and publish the derived ask ladders into the
+//! shared `Orderbook` that arbitrage detection reads.
+
+use std::collections::BTreeMap;
+
+use crate::types::{Contracts, Orderbook, Price, PriceLevel, Side};
+
+/// Resting size at each price for one side of a book
+#[derive(Debug, Clone, Default)]
+pub struct Ladder {
+    levels: BTreeMap<Price, u64>,
+}
+
+impl Ladder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the size resting at `price` (0 removes the level)
+    pub fn set(&mut self, price: Price, size: u64) {
+        if size == 0 {
+            self.levels.remove(&price);
+        } else {
+            self.levels.insert(price, size);
+        }
+    }
+
+    /// Add a signed size change to the level at `price` (floored at 0)
+    pub fn apply_delta(&mut self, price: Price, delta: i64) {
+        let current = self.levels.get(&price).copied().unwrap_or(0);
+        let size = if delta >= 0 {
+            current.saturating_add(delta.unsigned_abs())
+        } else {
+            current.saturating_sub(delta.unsigned_abs())
+        };
+        self.set(price, size);
+    }
+
+    pub fn clear(&mut self) {
+        self.levels.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// Highest price with resting size
+    pub fn best_high(&self) -> Option<(Price, u64)> {
+        self.levels.iter().next_back().map(|(&p, &s)| (p, s))
+    }
+
+    /// Levels from highest to lowest price (bid order)
+    pub fn descending(&self) -> impl Iterator<Item = (Price, u64)> + '_ {
+        self.levels.iter().rev().map(|(&p, &s)| (p, s))
+    }
+}
+
+/// Kalshi L2 book for a single ticker.
+///
+/// Kalshi only publishes resting bids. A YES bid at P is equivalent to a NO
+/// ask at 100 - P, so each side's ask ladder is derived from the opposite
+/// side's bids.
+#[derive(Debug, Clone, Default)]
+pub struct KalshiBook {
+    yes_bids: Ladder,
+    no_bids: Ladder,
+}
+
+impl KalshiBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the whole book with an `orderbook_snapshot`
+    pub fn apply_snapshot(&mut self, yes: &[(Price, u64)], no: &[(Price, u64)]) {
+        self.yes_bids.clear();
+        self.no_bids.clear();
+        for &(price, size) in yes {
+            self.yes_bids.set(price, size);
+        }
+        for &(price, size) in no {
+            self.no_bids.set(price, size);
+        }
+    }
+
+    /// Apply an `orderbook_delta` level change to one side's bids
+    pub fn apply_delta(&mut self, side: Side, price: Price, delta: i64) {
+        match side {
+            Side::Yes => self.yes_bids.apply_delta(price, delta),
+            Side::No => self.no_bids.apply_delta(price, delta),
+        }
+    }
+
+    pub fn yes_bids(&self) -> &Ladder {
+        &self.yes_bids
+    }
+
+    pub fn no_bids(&self) -> &Ladder {
+        &self.no_bids
+    }
+
+    /// Derived ask ladder for `side`, best (lowest) price first
+    pub fn asks(&self, side: Side) -> Vec<PriceLevel> {
+        let opposite = match side {
+            Side::Yes => &self.no_bids,
+            Side::No => &self.yes_bids,
+        };
+        opposite
+            .descending()
+            .filter(|&(price, _)| !price.is_zero() && price < Price::ONE)
+            .filter_map(|(price, size)| {
+                Some(PriceLevel {
+                    price: price.complement()?,
+                    size: Contracts(size),
+                })
+            })
+            .collect()
+    }
+
+    /// Build the normalized view published to `MarketState`
+    pub fn to_orderbook(&self) -> Orderbook {
+        let mut book = Orderbook {
+            tick_size: Some(Price::CENT),
+            ..Orderbook::default()
+        };
+        book.set_asks(Side::Yes, self.asks(Side::Yes));
+        book.set_asks(Side::No, self.asks(Side::No));
+        book
+    }
+}
+
+/// Which side of a Polymarket book a level rests on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSide {
+    Bid,
+    Ask,
+}
+
+/// Polymarket L2 book for a single outcome token.
+///
+/// Prices are kept exact (Polymarket quotes up to 4 decimals), so sub-cent
+/// levels stay distinct; sizes are fractional shares.
+#[derive(Debug, Clone, Default)]
+pub struct PolyBook {
+    bids: BTreeMap<Price, f64>,
+    asks: BTreeMap<Price, f64>,
+    /// Minimum price increment, from the last `tick_size_change`
+    pub tick_size: Option<Price>,
+    /// Price of the last trade, from `last_trade_price`
+    pub last_trade_price: Option<Price>,
+}
+
+impl PolyBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn side_mut(&mut self, side: BookSide) -> &mut BTreeMap<Price, f64> {
+        match side {
+            BookSide::Bid => &mut self.bids,
+            BookSide::Ask => &mut self.asks,
+        }
+    }
+
+    /// Replace the whole book with a `book` snapshot
+    pub fn apply_snapshot(&mut self, bids: &[(Price, f64)], asks: &[(Price, f64)]) {
+        self.bids.clear();
+        self.asks.clear();
+        for &(price, size) in bids {
+            self.set_level(BookSide::Bid, price, size);
+        }
+        for &(price, size) in asks {
+            self.set_level(BookSide::Ask, price, size);
+        }
+    }
+
+    /// Apply a `price_change` entry: `size` is the new total at `price`
+    pub fn set_level(&mut self, side: BookSide, price: Price, size: f64) {
+        let levels = self.side_mut(side);
+        if size > 0.0 {
+            levels.insert(price, size);
+        } else {
+            levels.remove(&price);
+        }
+    }
+
+    /// Best (highest) bid as (price, size)
+    pub fn best_bid(&self) -> Option<(Price, f64)> {
+        self.bids.iter().next_back().map(|(&p, &s)| (p, s))
+    }
+
+    /// Best (lowest) ask as (price, size)
+    pub fn best_ask(&self) -> Option<(Price, f64)> {
+        self.asks.iter().next().map(|(&p, &s)| (p, s))
+    }
+
+    /// Ask ladder, best (lowest) price first. Levels priced outside (0, 1)
+    /// or holding less than one whole contract are left out.
+    pub fn ask_levels(&self) -> Vec<PriceLevel> {
+        self.asks
+            .iter()
+            .filter(|&(&price, _)| !price.is_zero() && price < Price::ONE)
+            .filter_map(|(&price, &size)| {
+                let size = Contracts::from_shares(size).filter(|s| !s.is_zero())?;
+                Some(PriceLevel { price, size })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(cents: u32) -> Price {
+        Price::from_cents(cents)
+    }
+
+    fn d(decimal: &str) -> Price {
+        Price::parse_decimal(decimal).unwrap()
+    }
+
+    #[test]
+    fn test_kalshi_asks_derived_from_opposite_bids() {
+        let mut book = KalshiBook::new();
+        book.apply_snapshot(&[(c(8), 300), (c(22), 333)], &[(c(54), 20), (c(56), 146)]);
+
+        let ob = book.to_orderbook();
+        // Best NO bid 56 -> YES ask 44, best YES bid 22 -> NO ask 78
+        assert_eq!(ob.yes_ask, c(44));
+        assert_eq!(ob.yes_size, Contracts(146));
+        assert_eq!(ob.no_ask, c(78));
+        assert_eq!(ob.no_size, Contracts(333));
+        assert_eq!(
+            ob.yes_asks,
+            vec![
+                PriceLevel {
+                    price: c(44),
+                    size: Contracts(146)
+                },
+                PriceLevel {
+                    price: c(46),
+                    size: Contracts(20)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kalshi_delta_updates_and_removes_levels() {
+        let mut book = KalshiBook::new();
+        book.apply_snapshot(&[(c(22), 333)], &[(c(56), 146)]);
+
+        book.apply_delta(Side::No, c(56), -146);
+        book.apply_delta(Side::No, c(57), 10);
+        book.apply_delta(Side::Yes, c(22), 7);
+
+        assert_eq!(book.no_bids().best_high(), Some((c(57), 10)));
+        assert_eq!(book.yes_bids().best_high(), Some((c(22), 340)));
+
+        let ob = book.to_orderbook();
+        assert_eq!(ob.yes_ask, c(43));
+        assert_eq!(ob.yes_asks.len(), 1);
+    }
+
+    #[test]
+    fn test_poly_price_change_keeps_ladder_sorted() {
+        let mut book = PolyBook::new();
+        // Snapshot asks deliberately out of order
+        book.apply_snapshot(
+            &[(c(48), 10.0)],
+            &[(c(55), 5.0), (c(52), 3.0), (c(60), 1.0)],
+        );
+        assert_eq!(book.best_ask(), Some((c(52), 3.0)));
+
+        book.set_level(BookSide::Ask, c(51), 2.0);
+        book.set_level(BookSide::Ask, c(52), 0.0);
+        book.set_level(BookSide::Bid, c(50), 4.0);
+
+        assert_eq!(book.best_bid(), Some((c(50), 4.0)));
+        let prices: Vec<Price> = book.ask_levels().iter().map(|l| l.price).collect();
+        assert_eq!(prices, vec![c(51), c(55), c(60)]);
+    }
+
+    #[test]
+    fn test_large_sizes_do_not_wrap() {
+        let mut kalshi = KalshiBook::new();
+        kalshi.apply_snapshot(&[(c(40), 250_000)], &[(c(55), 70_000)]);
+        kalshi.apply_delta(Side::Yes, c(40), i64::MAX);
+        kalshi.apply_delta(Side::No, c(55), i64::MIN);
+        let ob = kalshi.to_orderbook();
+        assert_eq!(ob.no_size, Contracts(250_000 + i64::MAX as u64));
+        assert!(ob.yes_asks.is_empty());
+
+        let mut poly = PolyBook::new();
+        poly.apply_snapshot(&[], &[(c(52), 1_250_000.75), (d("0.521"), 10.5)]);
+        assert_eq!(poly.ask_levels()[0].size, Contracts(1_250_000));
+    }
+
+    #[test]
+    fn test_poly_sub_cent_levels_stay_distinct() {
+        let mut book = PolyBook::new();
+        book.apply_snapshot(
+            &[],
+            &[(d("0.999"), 40.0), (d("0.998"), 15.0), (d("0.0005"), 3.0)],
+        );
+        assert_eq!(
+            book.ask_levels(),
+            vec![
+                PriceLevel {
+                    price: d("0.0005"),
+                    size: Contracts(3)
+                },
+                PriceLevel {
+                    price: d("0.998"),
+                    size: Contracts(15)
+                },
+                PriceLevel {
+                    price: d("0.999"),
+                    size: Contracts(40)
+                },
+            ]
+        );
+    }
+}
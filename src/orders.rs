@@ -0,0 +1,403 @@
+//! Orders and their lifecycle, shared by every venue.
+//!
+//! Every order moves through one explicit state machine:
+//!
+//! ```text
+//! New -> Sent -> Acked -> PartiallyFilled -> Filled
+//!          |       |            |
+//!          +-------+------------+---------> Cancelled / Rejected / Expired
+//! ```
+//!
+//! A `Cancelled` order still takes fills up to its quantity: a fill that
+//! traded before the cancel may be reported after it.
+//!
+//! Venue clients report what happened as [`OrderEvent`]s; the
+//! [`OrderManager`] applies them, refuses impossible transitions and passes
+//! every accepted event to its subscribed [`OrderHook`]s.
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::types::{Contracts, Leg, Notional, Price, Venue};
+
+/// How long an order rests before the venue cancels it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeInForce {
+    /// Fill what is available now, cancel the rest
+    ImmediateOrCancel,
+    /// Fill completely now or not at all
+    FillOrKill,
+    GoodTillCancelled,
+    /// Rest until the given time, then expire
+    GoodTillTime(DateTime<Utc>),
+}
+
+/// Whether an order buys or sells its leg
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderAction {
+    Buy,
+    /// Sell contracts already held, e.g. to unwind an unhedged leg
+    Sell,
+}
+
+impl std::fmt::Display for OrderAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderAction::Buy => write!(f, "buy"),
+            OrderAction::Sell => write!(f, "sell"),
+        }
+    }
+}
+
+/// Where an order is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderState {
+    /// Created locally, not yet sent
+    New,
+    /// Sent, not yet acknowledged
+    Sent,
+    /// Resting on the venue's book
+    Acked,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+    Expired,
+}
+
+impl OrderState {
+    /// Whether the order can still change
+    pub fn is_open(self) -> bool {
+        matches!(
+            self,
+            OrderState::New | OrderState::Sent | OrderState::Acked | OrderState::PartiallyFilled
+        )
+    }
+}
+
+impl std::fmt::Display for OrderState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderState::New => write!(f, "new"),
+            OrderState::Sent => write!(f, "sent"),
+            OrderState::Acked => write!(f, "acked"),
+            OrderState::PartiallyFilled => write!(f, "partially filled"),
+            OrderState::Filled => write!(f, "filled"),
+            OrderState::Cancelled => write!(f, "cancelled"),
+            OrderState::Rejected => write!(f, "rejected"),
+            OrderState::Expired => write!(f, "expired"),
+        }
+    }
+}
+
+/// Something that happened to an order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderEvent {
+    /// Handed to the venue
+    Sent,
+    /// Accepted by the venue
+    Acked {
+        venue_order_id: String,
+    },
+    /// Some or all of the order traded
+    Fill {
+        quantity: Contracts,
+        price: Price,
+        fee: Notional,
+    },
+    /// The unfilled remainder was cancelled (by us, or by an IOC order's
+    /// venue)
+    Cancelled,
+    Rejected {
+        reason: String,
+    },
+    /// A good-till-time order ran out
+    Expired,
+}
+
+/// An order for one leg
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Order {
+    /// Our id, unique across restarts
+    pub client_order_id: String,
+    /// The venue's id, once acknowledged
+    pub venue_order_id: Option<String>,
+    /// Tracked market the order belongs to
+    pub market_id: String,
+    /// Venue, instrument and side to trade
+    pub leg: Leg,
+    pub action: OrderAction,
+    /// Limit price: the most to pay, or the least to accept when selling
+    pub price: Price,
+    pub quantity: Contracts,
+    pub time_in_force: TimeInForce,
+    pub state: OrderState,
+    pub filled: Contracts,
+    /// Paid (or, when selling, received) for the filled contracts, before
+    /// fees
+    pub cost: Notional,
+    pub fee: Notional,
+    pub reject_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Order {
+    pub fn venue(&self) -> Venue {
+        self.leg.venue
+    }
+
+    /// Contracts not yet filled
+    pub fn remaining(&self) -> Contracts {
+        self.quantity.saturating_sub(self.filled)
+    }
+
+    /// Everything paid, fees included; negative for a sale's net proceeds
+    pub fn total_cost(&self) -> Notional {
+        match self.action {
+            OrderAction::Buy => self.cost.saturating_add(self.fee),
+            OrderAction::Sell => self.fee.saturating_sub(self.cost),
+        }
+    }
+
+    /// Move to the state `event` leads to, or fail if it cannot happen now
+    pub fn apply(&mut self, event: &OrderEvent) -> Result<()> {
+        use OrderState::*;
+        let next = match (self.state, event) {
+            (New, OrderEvent::Sent) => Sent,
+            (Sent, OrderEvent::Acked { venue_order_id }) => {
+                self.venue_order_id = Some(venue_order_id.clone());
+                Acked
+            }
+            // Venues may report an immediate fill before (or instead of) the ack
+            (
+                Sent | Acked | PartiallyFilled,
+                OrderEvent::Fill {
+                    quantity,
+                    price,
+                    fee,
+                },
+            ) => {
+                self.record_fill(*quantity, *price, *fee)?;
+                if self.remaining().is_zero() {
+                    Filled
+                } else {
+                    PartiallyFilled
+                }
+            }
+            // A fill that traded before the cancel can be reported after it
+            (
+                Cancelled,
+                OrderEvent::Fill {
+                    quantity,
+                    price,
+                    fee,
+                },
+            ) => {
+                self.record_fill(*quantity, *price, *fee)?;
+                Cancelled
+            }
+            (Sent | Acked | PartiallyFilled, OrderEvent::Cancelled) => Cancelled,
+            (New | Sent | Acked, OrderEvent::Rejected { reason }) => {
+                self.reject_reason = Some(reason.clone());
+                Rejected
+            }
+            (Acked | PartiallyFilled, OrderEvent::Expired) => Expired,
+            (state, event) => bail!(
+                "order {} cannot go from {} on {:?}",
+                self.client_order_id,
+                state,
+                event
+            ),
+        };
+        self.state = next;
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Add a fill of at most the unfilled remainder
+    fn record_fill(&mut self, quantity: Contracts, price: Price, fee: Notional) -> Result<()> {
+        if quantity.is_zero() || quantity > self.remaining() {
+            bail!(
+                "fill of {} on {} with {} remaining",
+                quantity,
+                self.client_order_id,
+                self.remaining()
+            );
+        }
+        let cost = quantity
+            .notional(price)
+            .ok_or_else(|| anyhow!("fill notional overflow"))?;
+        self.filled = self.filled.saturating_add(quantity);
+        self.cost = self.cost.saturating_add(cost);
+        self.fee = self.fee.saturating_add(fee);
+        Ok(())
+    }
+}
+
+/// Subscriber to every accepted order event
+pub trait OrderHook: Send + Sync {
+    /// Called after `event` moved `order` to its current state
+    fn on_order_event(&self, order: &Order, event: &OrderEvent);
+}
+
+/// Every order placed this session, by client order id
+pub struct OrderManager {
+    orders: Mutex<HashMap<String, Order>>,
+    hooks: RwLock<Vec<Arc<dyn OrderHook>>>,
+    /// Session prefix keeping client order ids unique across restarts
+    session: i64,
+    next_id: AtomicU64,
+}
+
+impl Default for OrderManager {
+    fn default() -> Self {
+        Self {
+            orders: Mutex::new(HashMap::new()),
+            hooks: RwLock::new(Vec::new()),
+            session: Utc::now().timestamp_millis(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+}
+
+impl OrderManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receive every order event from now on
+    pub fn subscribe(&self, hook: Arc<dyn OrderHook>) {
+        self.hooks.write().unwrap().push(hook);
+    }
+
+    /// Create a `New` order and start tracking it
+    pub fn create(
+        &self,
+        market_id: &str,
+        leg: Leg,
+        action: OrderAction,
+        price: Price,
+        quantity: Contracts,
+        time_in_force: TimeInForce,
+    ) -> Order {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let now = Utc::now();
+        let order = Order {
+            client_order_id: format!("arb-{}-{}", self.session, id),
+            venue_order_id: None,
+            market_id: market_id.to_string(),
+            leg,
+            action,
+            price,
+            quantity,
+            time_in_force,
+            state: OrderState::New,
+            filled: Contracts::ZERO,
+            cost: Notional::ZERO,
+            fee: Notional::ZERO,
+            reject_reason: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let mut orders = self.orders.lock().unwrap();
+        orders.insert(order.client_order_id.clone(), order.clone());
+        order
+    }
+
+    /// Apply `event` to an order and notify the hooks; returns the updated
+    /// order
+    pub fn apply(&self, client_order_id: &str, event: OrderEvent) -> Result<Order> {
+        let order = {
+            let mut orders = self.orders.lock().unwrap();
+            let order = orders
+                .get_mut(client_order_id)
+                .ok_or_else(|| anyhow!("unknown order {}", client_order_id))?;
+            order.apply(&event)?;
+            order.clone()
+        };
+        for hook in self.hooks.read().unwrap().iter() {
+            hook.on_order_event(&order, &event);
+        }
+        Ok(order)
+    }
+
+    pub fn get(&self, client_order_id: &str) -> Option<Order> {
+        self.orders.lock().unwrap().get(client_order_id).cloned()
+    }
+
+    /// Orders that can still change
+    pub fn open_orders(&self) -> Vec<Order> {
+        let orders = self.orders.lock().unwrap();
+        orders
+            .values()
+            .filter(|o| o.state.is_open())
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::get_hardcoded_markets;
+    use crate::position_tracker::PositionTracker;
+    use crate::types::Side;
+
+    #[test]
+    fn test_order_state_machine() {
+        let manager = OrderManager::new();
+        let tracker = Arc::new(RwLock::new(PositionTracker::new()));
+        manager.subscribe(tracker.clone());
+        let pair = get_hardcoded_markets().remove(0);
+        let leg = pair.leg(Venue::Kalshi, Side::Yes);
+        let order = manager.create(
+            &pair.id,
+            leg.clone(),
+            OrderAction::Buy,
+            Price::from_cents(40),
+            Contracts(10),
+            TimeInForce::ImmediateOrCancel,
+        );
+        let id = order.client_order_id.as_str();
+
+        // Nothing can fill before the order is sent
+        let fill = |quantity| OrderEvent::Fill {
+            quantity: Contracts(quantity),
+            price: Price::from_cents(40),
+            fee: Notional::ZERO,
+        };
+        assert!(manager.apply(id, fill(4)).is_err());
+
+        manager.apply(id, OrderEvent::Sent).unwrap();
+        let acked = OrderEvent::Acked {
+            venue_order_id: "k-1".into(),
+        };
+        manager.apply(id, acked).unwrap();
+        let order = manager.apply(id, fill(4)).unwrap();
+        assert_eq!(order.state, OrderState::PartiallyFilled);
+        assert!(manager.apply(id, fill(7)).is_err()); // More than remains
+        assert_eq!(manager.open_orders().len(), 1);
+
+        let order = manager.apply(id, OrderEvent::Cancelled).unwrap();
+        assert_eq!(order.state, OrderState::Cancelled);
+        assert_eq!(order.cost, Notional::from_cents(160));
+        assert!(manager.open_orders().is_empty());
+
+        // A fill reported after the cancel still counts, up to the quantity
+        assert!(manager.apply(id, fill(7)).is_err());
+        let order = manager.apply(id, fill(1)).unwrap();
+        assert_eq!(order.state, OrderState::Cancelled);
+        assert_eq!(order.filled, Contracts(5));
+
+        // The position tracker saw each fill as it happened, late one included
+        let held = tracker
+            .read()
+            .unwrap()
+            .holding(Venue::Kalshi, &leg.instrument, Side::Yes);
+        assert_eq!(held, Contracts(5));
+    }
+}
@@ -3,194 +3,302 @@
 //! MVP version: connects to public WebSocket feed, no authentication.
 //! Does NOT support order execution (would require CLOB client integration).
 
-use anyhow::{Context, Result};
-use futures_util::{SinkExt, StreamExt};
-use serde_json::Value;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
-use tracing::{debug, error, info, warn};
-
-use crate::types::{price_to_cents, MarketState, Orderbook, PriceCents, SizeCents};
-
-/// Polymarket WebSocket URL (public orderbook feed)
-const POLYMARKET_WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
-
-/// Run Polymarket WebSocket connection
-pub async fn run_polymarket_ws(
-    markets: Arc<RwLock<HashMap<String, Arc<MarketState>>>>,
-) -> Result<()> {
-    info!("[POLYMARKET] Connecting to WebSocket: {}", POLYMARKET_WS_URL);
-
-    let (ws_stream, _) = connect_async(POLYMARKET_WS_URL)
-        .await
-        .context("Failed to connect to Polymarket WebSocket")?;
-
-    info!("[POLYMARKET] ✅ Connected to WebSocket");
-
-    let (mut write, mut read) = ws_stream.split();
-
-    // Subscribe to orderbook updates for all tracked markets
-    let markets_guard = markets.read().unwrap();
-    let token_ids: Vec<String> = markets_guard
-        .values()
-        .flat_map(|m| vec![m.pair.poly_yes_token.clone(), m.pair.poly_no_token.clone()])
-        .collect();
-    drop(markets_guard);
-
-    if !token_ids.is_empty() {
-        for token_id in &token_ids {
-            let subscribe_msg = serde_json::json!({
-                "type": "subscribe",
-                "channel": "book",
-                "market": token_id,
-            });
-
-            write
-                .send(Message::Text(subscribe_msg.to_string()))
-                .await
-                .context("Failed to send subscribe message")?;
-        }
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+use crate::feed::{truncate, FeedClient, FeedEvent};
+use crate::orderbook::{BookSide, PolyBook};
+use crate::polymarket_messages::{
+    parse_frame, Book, Event, LastTradePrice, OrderSide, PriceChange, TickSizeChange,
+};
+use crate::registry::RoutingIndex;
+use crate::types::{Price, Venue};
+
+/// Polymarket market-channel feed adapter
+#[derive(Debug, Default)]
+pub struct PolymarketFeed {
+    /// WebSocket endpoint
+    url: String,
+    /// Token id -> (market, side) routing for the current subscription
+    routes: Arc<RoutingIndex>,
+    /// L2 books keyed by token id (rebuilt from snapshots)
+    books: HashMap<String, PolyBook>,
+}
 
-        info!("[POLYMARKET] Subscribed to {} tokens", token_ids.len());
+impl PolymarketFeed {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            ..Self::default()
+        }
     }
 
-    // Read messages
-    while let Some(msg) = read.next().await {
-        match msg {
-            Ok(Message::Text(text)) => {
-                if let Err(e) = handle_polymarket_message(&text, &markets) {
-                    warn!("[POLYMARKET] Error handling message: {}", e);
-                }
+    /// Handle a single Polymarket event
+    fn handle_event(&mut self, event: Event) -> Result<Vec<FeedEvent>> {
+        match event {
+            Event::Book(book) => Ok(self.handle_book_update(book)),
+            Event::PriceChange(change) => self.handle_price_change(change),
+            Event::TickSizeChange(change) => Ok(self.handle_tick_size_change(change)),
+            Event::LastTradePrice(trade) => {
+                self.handle_last_trade_price(trade);
+                Ok(Vec::new())
             }
-            Ok(Message::Ping(data)) => {
-                debug!("[POLYMARKET] Received ping, sending pong");
-                if let Err(e) = write.send(Message::Pong(data)).await {
-                    error!("[POLYMARKET] Failed to send pong: {}", e);
-                }
+            Event::Subscribed => {
+                debug!("[POLYMARKET] Subscription confirmed");
+                Ok(Vec::new())
             }
-            Ok(Message::Close(_)) => {
-                warn!("[POLYMARKET] WebSocket closed by server");
-                break;
+            Event::Error(body) => {
+                warn!(
+                    "[POLYMARKET] Error message received: {}",
+                    truncate(body, 200)
+                );
+                Ok(Vec::new())
             }
-            Err(e) => {
-                error!("[POLYMARKET] WebSocket error: {}", e);
-                break;
+            Event::Unknown => {
+                debug!("[POLYMARKET] Unknown event type");
+                Ok(Vec::new())
             }
-            _ => {}
         }
     }
 
-    warn!("[POLYMARKET] WebSocket disconnected");
-    Ok(())
+    /// Handle full book snapshot for one token
+    fn handle_book_update(&mut self, msg: Book) -> Vec<FeedEvent> {
+        let token_id = msg.asset_id.as_ref();
+        if self.routes.poly(token_id).is_none() {
+            return Vec::new(); // Market not tracked
+        }
+
+        let bids: Vec<(Price, f64)> = msg.bids.iter().map(|l| (l.price, l.size)).collect();
+        let asks: Vec<(Price, f64)> = msg.asks.iter().map(|l| (l.price, l.size)).collect();
+        let book = self.books.entry(token_id.to_string()).or_default();
+        book.apply_snapshot(&bids, &asks);
+
+        self.book_event(token_id, msg.timestamp)
+            .into_iter()
+            .collect()
+    }
+
+    /// Handle incremental price level changes
+    fn handle_price_change(&mut self, msg: PriceChange) -> Result<Vec<FeedEvent>> {
+        let entries = msg.entries()?;
+
+        // Apply every change first, then publish each touched token once
+        let mut touched: Vec<&str> = Vec::new();
+        for (token_id, change) in entries {
+            let book_side = match change.side {
+                OrderSide::Buy => BookSide::Bid,
+                OrderSide::Sell => BookSide::Ask,
+            };
+
+            // Deltas are only meaningful on top of a snapshot
+            match self.books.get_mut(token_id) {
+                Some(book) => book.set_level(book_side, change.price, change.size),
+                None => {
+                    debug!(
+                        "[POLYMARKET] Change for {} before snapshot, ignoring",
+                        token_id
+                    );
+                    continue;
+                }
+            }
+            if !touched.contains(&token_id) {
+                touched.push(token_id);
+            }
+        }
+
+        Ok(touched
+            .into_iter()
+            .filter_map(|token_id| self.book_event(token_id, msg.timestamp))
+            .collect())
+    }
+
+    /// Handle tick size change (book prices are unaffected, but the
+    /// published book carries the new tick)
+    fn handle_tick_size_change(&mut self, msg: TickSizeChange) -> Vec<FeedEvent> {
+        let token_id = msg.asset_id.as_ref();
+        let Some(book) = self.books.get_mut(token_id) else {
+            return Vec::new();
+        };
+        info!(
+            "[POLYMARKET] Tick size for {} changed: {:?} -> {}",
+            msg.asset_id,
+            book.tick_size.or(msg.old_tick_size),
+            msg.new_tick_size
+        );
+        book.tick_size = Some(msg.new_tick_size);
+        self.book_event(token_id, None).into_iter().collect()
+    }
+
+    /// Handle last trade price (informational, does not change the book)
+    fn handle_last_trade_price(&mut self, msg: LastTradePrice) {
+        if let Some(book) = self.books.get_mut(msg.asset_id.as_ref()) {
+            book.last_trade_price = Some(msg.price);
+        }
+        debug!(
+            "[POLYMARKET] Last trade for {}: {}",
+            msg.asset_id, msg.price
+        );
+    }
+
+    /// Ask ladder event for one token's side of its market
+    fn book_event(&self, token_id: &str, exchange_ts: Option<DateTime<Utc>>) -> Option<FeedEvent> {
+        let (market, side) = self.routes.poly(token_id)?;
+        let book = self.books.get(token_id)?;
+        Some(FeedEvent::Asks {
+            market: market.clone(),
+            side: *side,
+            levels: book.ask_levels(),
+            tick_size: book.tick_size,
+            exchange_ts,
+        })
+    }
 }
 
-/// Handle incoming Polymarket message
-fn handle_polymarket_message(
-    text: &str,
-    markets: &Arc<RwLock<HashMap<String, Arc<MarketState>>>>,
-) -> Result<()> {
-    let msg: Value = serde_json::from_str(text).context("Failed to parse JSON")?;
-
-    // Check event type
-    let event_type = msg
-        .get("event_type")
-        .and_then(|v| v.as_str())
-        .unwrap_or("unknown");
-
-    match event_type {
-        "book" => {
-            handle_book_update(&msg, markets)?;
+impl FeedClient for PolymarketFeed {
+    fn venue(&self) -> Venue {
+        Venue::Polymarket
+    }
+
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn subscribe(&mut self, routes: Arc<RoutingIndex>) -> Vec<String> {
+        // Books do not survive a reconnect
+        self.books.clear();
+        self.routes = routes;
+
+        let token_ids: Vec<&String> = self.routes.poly_tokens().collect();
+        if token_ids.is_empty() {
+            return Vec::new();
         }
-        "subscribed" => {
-            debug!("[POLYMARKET] Subscription confirmed");
+
+        // One market-channel subscription covers every token; the server
+        // answers with a `book` snapshot per token, then `price_change` deltas
+        let subscribe_msg = serde_json::json!({
+            "type": "market",
+            "assets_ids": token_ids,
+        });
+        vec![subscribe_msg.to_string()]
+    }
+
+    /// Decode a single event or an array of events
+    fn decode(&mut self, text: &str) -> Result<Vec<FeedEvent>> {
+        // Reply to our application-level PING
+        if text == "PONG" {
+            return Ok(Vec::new());
         }
-        "error" => {
-            warn!("[POLYMARKET] Error message: {:?}", msg);
+
+        // A bad element of an array is skipped; the frame is only rejected
+        // when nothing in it could be used
+        let mut decoded = Vec::new();
+        let mut errors = Vec::new();
+        let mut any_ok = false;
+        for event in parse_frame(text)? {
+            match event.and_then(|event| self.handle_event(event)) {
+                Ok(events) => {
+                    any_ok = true;
+                    decoded.extend(events);
+                }
+                Err(e) => errors.push(e),
+            }
         }
-        _ => {
-            debug!("[POLYMARKET] Unknown event type: {}", event_type);
+        if !any_ok {
+            if let Some(e) = errors.into_iter().next() {
+                return Err(e);
+            }
+        } else {
+            for e in errors {
+                warn!("[POLYMARKET] Skipping bad event: {:#}", e);
+            }
         }
+        Ok(decoded)
     }
 
-    Ok(())
+    /// The market channel expects a literal `PING` text frame
+    fn ping_frame(&self) -> Option<String> {
+        Some("PING".into())
+    }
 }
 
-/// Handle book update
-fn handle_book_update(
-    msg: &Value,
-    markets: &Arc<RwLock<HashMap<String, Arc<MarketState>>>>,
-) -> Result<()> {
-    let token_id = msg
-        .get("market")
-        .and_then(|v| v.as_str())
-        .context("Missing market/token_id")?;
-
-    let markets_guard = markets.read().unwrap();
-    let market_state = markets_guard.values().find(|m| {
-        m.pair.poly_yes_token == token_id || m.pair.poly_no_token == token_id
-    });
-
-    let market_state = match market_state {
-        Some(m) => m.clone(),
-        None => return Ok(()), // Market not tracked
-    };
-    drop(markets_guard);
-
-    let is_yes = market_state.pair.poly_yes_token == token_id;
-
-    // Parse best ask price (Polymarket uses "0.XX" format)
-    let asks = msg.get("asks").and_then(|v| v.as_array());
-    let best_ask_price: PriceCents = if let Some(asks) = asks {
-        asks.first()
-            .and_then(|order| order.get("price"))
-            .and_then(|p| p.as_str())
-            .map(|s| {
-                s.parse::<f64>()
-                    .ok()
-                    .map(price_to_cents)
-                    .unwrap_or(0)
-            })
-            .unwrap_or(0)
-    } else {
-        0
-    };
-
-    // Parse best ask size (in dollars, convert to cents)
-    let best_ask_size: SizeCents = if let Some(asks) = asks {
-        asks.first()
-            .and_then(|order| order.get("size"))
-            .and_then(|s| s.as_str())
-            .map(|s| {
-                s.parse::<f64>()
-                    .ok()
-                    .map(|sz| (sz * 100.0) as u16)
-                    .unwrap_or(0)
-            })
-            .unwrap_or(0)
-    } else {
-        0
-    };
-
-    // Update market state
-    {
-        let mut book = market_state.poly.write().unwrap();
-        if is_yes {
-            book.yes_ask = best_ask_price;
-            book.yes_size = best_ask_size;
-        } else {
-            book.no_ask = best_ask_price;
-            book.no_size = best_ask_size;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::MarketRegistry;
+    use crate::types::{Contracts, PriceLevel, Side};
+
+    #[test]
+    fn test_book_and_price_change_frames() {
+        let mut pair = crate::config::get_hardcoded_markets().remove(0);
+        pair.id = "test-market".into();
+        pair.poly_yes_token = "111".into();
+        pair.poly_no_token = "222".into();
+        let registry = MarketRegistry::new();
+        registry.add(pair);
+        let mut feed = PolymarketFeed::new("ws://localhost");
+        feed.subscribe(registry.routes());
+
+        let book = r#"[{"event_type":"book","asset_id":"222","market":"0xabc","timestamp":"1729084877448","bids":[{"price":"0.40","size":"10"}],"asks":[{"price":"0.58","size":"5"},{"price":"0.56","size":"2"}]}]"#;
+        let events = feed.decode(book).unwrap();
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            FeedEvent::Asks {
+                market,
+                side,
+                levels,
+                exchange_ts,
+                ..
+            } => {
+                assert_eq!(market.pair.id, "test-market");
+                assert_eq!(*side, Side::No);
+                assert_eq!(
+                    levels,
+                    &vec![
+                        PriceLevel {
+                            price: Price::from_cents(56),
+                            size: Contracts(2)
+                        },
+                        PriceLevel {
+                            price: Price::from_cents(58),
+                            size: Contracts(5)
+                        },
+                    ]
+                );
+                assert_eq!(*exchange_ts, DateTime::from_timestamp_millis(1729084877448));
+            }
+            other => panic!("unexpected event {:?}", other),
         }
-    }
 
-    debug!(
-        "[POLYMARKET] {} | {} ask: {}¢ ({}¢)",
-        market_state.pair.description,
-        if is_yes { "YES" } else { "NO" },
-        best_ask_price,
-        best_ask_size
-    );
+        let change = r#"{"event_type":"price_change","market":"0xabc","price_changes":[{"asset_id":"222","price":"0.56","size":"0","side":"SELL"},{"asset_id":"222","price":"0.55","size":"1","side":"SELL"},{"asset_id":"111","price":"0.30","size":"1","side":"SELL"}]}"#;
+        let events = feed.decode(change).unwrap();
+        // Token 111 has no snapshot yet, so only 222 is published
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            FeedEvent::Asks { levels, .. } => {
+                let best = PriceLevel {
+                    price: Price::from_cents(55),
+                    size: Contracts(1),
+                };
+                assert_eq!(levels[0], best);
+                assert_eq!(levels[1].price, Price::from_cents(58));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        let tick = r#"{"event_type":"tick_size_change","asset_id":"222","old_tick_size":"0.01","new_tick_size":"0.001"}"#;
+        match &feed.decode(tick).unwrap()[0] {
+            FeedEvent::Asks { tick_size, .. } => {
+                assert_eq!(*tick_size, Price::parse_decimal("0.001"));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
 
-    Ok(())
+        // A malformed element costs only itself, not the rest of the array
+        let mixed = r#"[{"event_type":"book","asset_id":"111","bids":[],"asks":[{"size":"1"}]},{"event_type":"book","asset_id":"111","bids":[],"asks":[{"price":"0.30","size":"4"}]}]"#;
+        assert_eq!(feed.decode(mixed).unwrap().len(), 1);
+        assert!(feed.decode(r#"[{"event_type":"book"}]"#).is_err());
+    }
 }
@@ -0,0 +1,344 @@
+//! Polymarket CLOB market-channel WebSocket message models.
+//!
+//! Each event's `event_type` is read first, then the event is decoded
+//! straight from its slice of the frame into the matching type, borrowing
+//! ids where possible; no `serde_json::Value` or buffered content tree is
+//! built.
+//! Decimal fields arrive as strings ("0.52") and are validated on decode, so
+//! a missing or out-of-range price rejects the frame instead of becoming 0.
+//! Prices are parsed exactly into [`Price`]; sub-cent ticks ("0.001") keep
+//! their precision.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::{self, Deserializer, Visitor};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::types::Price;
+
+/// One market-channel event
+#[derive(Debug)]
+pub enum Event<'a> {
+    Book(Book<'a>),
+    PriceChange(PriceChange<'a>),
+    TickSizeChange(TickSizeChange<'a>),
+    LastTradePrice(LastTradePrice<'a>),
+    Subscribed,
+    /// The whole error event, as the venue sent it
+    Error(&'a str),
+    Unknown,
+}
+
+/// Just the tag of an event; every other field is skipped unbuffered
+#[derive(Deserialize)]
+struct EventType<'a> {
+    #[serde(borrow)]
+    event_type: Cow<'a, str>,
+}
+
+/// Decode one event: read its `event_type`, then decode the matching type
+pub fn parse_event(text: &str) -> Result<Event<'_>> {
+    let tag: EventType = serde_json::from_str(text).context("Malformed Polymarket event")?;
+    let event = match tag.event_type.as_ref() {
+        "book" => Event::Book(serde_json::from_str(text)?),
+        "price_change" => Event::PriceChange(serde_json::from_str(text)?),
+        "tick_size_change" => Event::TickSizeChange(serde_json::from_str(text)?),
+        "last_trade_price" => Event::LastTradePrice(serde_json::from_str(text)?),
+        "subscribed" => Event::Subscribed,
+        "error" => Event::Error(text),
+        _ => Event::Unknown,
+    };
+    Ok(event)
+}
+
+/// Decode a frame holding one event or an array of events.
+///
+/// Each element of an array decodes on its own, so one malformed event
+/// comes back as an error in its place without losing the others.
+pub fn parse_frame(text: &str) -> Result<Vec<Result<Event<'_>>>> {
+    if text.trim_start().starts_with('[') {
+        let elements: Vec<&RawValue> =
+            serde_json::from_str(text).context("Malformed Polymarket event array")?;
+        Ok(elements
+            .into_iter()
+            .map(|element| parse_event(element.get()))
+            .collect())
+    } else {
+        Ok(vec![parse_event(text)])
+    }
+}
+
+/// Side of the book a change applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+/// One price level
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Level {
+    #[serde(deserialize_with = "probability")]
+    pub price: Price,
+    #[serde(deserialize_with = "quantity")]
+    pub size: f64,
+}
+
+/// `book`: full snapshot for one token
+#[derive(Debug, Deserialize)]
+pub struct Book<'a> {
+    #[serde(borrow)]
+    pub asset_id: Cow<'a, str>,
+    pub bids: Vec<Level>,
+    pub asks: Vec<Level>,
+    #[serde(default, deserialize_with = "timestamp")]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// One level update inside a `price_change`
+#[derive(Debug, Deserialize)]
+pub struct PriceChangeEntry<'a> {
+    /// Present in batched changes; older single-token frames omit it
+    #[serde(borrow)]
+    pub asset_id: Option<Cow<'a, str>>,
+    #[serde(deserialize_with = "probability")]
+    pub price: Price,
+    /// New total size at `price` (0 removes the level)
+    #[serde(deserialize_with = "quantity")]
+    pub size: f64,
+    pub side: OrderSide,
+}
+
+/// `price_change`: level updates for one or more tokens.
+///
+/// Current feeds batch changes for several tokens under `price_changes`;
+/// older feeds send one token's `changes` with a top-level `asset_id`.
+#[derive(Debug, Deserialize)]
+pub struct PriceChange<'a> {
+    #[serde(borrow)]
+    pub asset_id: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub price_changes: Option<Vec<PriceChangeEntry<'a>>>,
+    #[serde(borrow)]
+    pub changes: Option<Vec<PriceChangeEntry<'a>>>,
+    #[serde(default, deserialize_with = "timestamp")]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl PriceChange<'_> {
+    /// Every change paired with the token it applies to
+    pub fn entries(&self) -> Result<Vec<(&str, &PriceChangeEntry<'_>)>> {
+        let (changes, default_token) = match (&self.price_changes, &self.changes) {
+            (Some(changes), _) => (changes, None),
+            (None, Some(changes)) => (changes, self.asset_id.as_deref()),
+            (None, None) => anyhow::bail!("price_change has neither price_changes nor changes"),
+        };
+        changes
+            .iter()
+            .map(|change| {
+                let token_id = change
+                    .asset_id
+                    .as_deref()
+                    .or(default_token)
+                    .context("price_change entry missing asset_id")?;
+                Ok((token_id, change))
+            })
+            .collect()
+    }
+}
+
+/// `tick_size_change`: minimum price increment changed
+#[derive(Debug, Deserialize)]
+pub struct TickSizeChange<'a> {
+    #[serde(borrow)]
+    pub asset_id: Cow<'a, str>,
+    #[serde(default, deserialize_with = "optional_probability")]
+    pub old_tick_size: Option<Price>,
+    #[serde(deserialize_with = "probability")]
+    pub new_tick_size: Price,
+}
+
+/// `last_trade_price`: one execution
+#[derive(Debug, Deserialize)]
+pub struct LastTradePrice<'a> {
+    #[serde(borrow)]
+    pub asset_id: Cow<'a, str>,
+    #[serde(deserialize_with = "probability")]
+    pub price: Price,
+    #[serde(default, deserialize_with = "optional_quantity")]
+    pub size: Option<f64>,
+    pub side: Option<OrderSide>,
+    #[serde(default, deserialize_with = "timestamp")]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// A decimal sent as a JSON string ("0.52") or number
+fn decimal<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    struct DecimalVisitor;
+
+    impl<'de> Visitor<'de> for DecimalVisitor {
+        type Value = f64;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a decimal string or number")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<f64, E> {
+            match s.parse::<f64>() {
+                Ok(v) if v.is_finite() => Ok(v),
+                _ => Err(E::custom(format_args!("invalid decimal {:?}", s))),
+            }
+        }
+
+        fn visit_f64<E: de::Error>(self, v: f64) -> Result<f64, E> {
+            Ok(v)
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<f64, E> {
+            Ok(v as f64)
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<f64, E> {
+            Ok(v as f64)
+        }
+    }
+
+    d.deserialize_any(DecimalVisitor)
+}
+
+/// A price, which must lie in [0, 1] on the [`Price`] grid
+fn probability<'de, D: Deserializer<'de>>(d: D) -> Result<Price, D::Error> {
+    struct PriceVisitor;
+
+    impl<'de> Visitor<'de> for PriceVisitor {
+        type Value = Price;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a decimal price string or number")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Price, E> {
+            match Price::parse_decimal(s) {
+                Some(price) if price <= Price::ONE => Ok(price),
+                Some(_) => Err(E::custom(format_args!("price {} outside 0..=1", s))),
+                None => Err(E::custom(format_args!("invalid price {:?}", s))),
+            }
+        }
+
+        fn visit_f64<E: de::Error>(self, v: f64) -> Result<Price, E> {
+            match Price::from_dollars(v) {
+                Some(price) if price <= Price::ONE => Ok(price),
+                _ => Err(E::custom(format_args!("price {} outside 0..=1", v))),
+            }
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Price, E> {
+            self.visit_f64(v as f64)
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Price, E> {
+            self.visit_f64(v as f64)
+        }
+    }
+
+    d.deserialize_any(PriceVisitor)
+}
+
+/// A size, which must not be negative
+fn quantity<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    let size = decimal(d)?;
+    if size >= 0.0 {
+        Ok(size)
+    } else {
+        Err(de::Error::custom(format_args!("negative size {}", size)))
+    }
+}
+
+fn optional_probability<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Price>, D::Error> {
+    probability(d).map(Some)
+}
+
+fn optional_quantity<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+    quantity(d).map(Some)
+}
+
+/// Exchange timestamp in Unix milliseconds ("1729084877448")
+fn timestamp<'de, D: Deserializer<'de>>(d: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+    struct MillisVisitor;
+
+    impl<'de> Visitor<'de> for MillisVisitor {
+        type Value = Option<DateTime<Utc>>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("Unix milliseconds as a string or number")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            let millis = s
+                .parse::<i64>()
+                .map_err(|_| E::custom(format_args!("invalid timestamp {:?}", s)))?;
+            self.visit_i64(millis)
+        }
+
+        fn visit_u64<E: de::Error>(self, millis: u64) -> Result<Self::Value, E> {
+            let millis = i64::try_from(millis).map_err(E::custom)?;
+            self.visit_i64(millis)
+        }
+
+        fn visit_i64<E: de::Error>(self, millis: i64) -> Result<Self::Value, E> {
+            DateTime::from_timestamp_millis(millis)
+                .map(Some)
+                .ok_or_else(|| E::custom(format_args!("timestamp {} out of range", millis)))
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+    }
+
+    d.deserialize_any(MillisVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_malformed_events() {
+        let no_price = r#"{"event_type":"book","asset_id":"1","bids":[{"size":"10"}],"asks":[]}"#;
+        let err = parse_event(no_price).unwrap_err();
+        assert!(format!("{:#}", err).contains("missing field `price`"));
+
+        // Only the bad element of an array is lost
+        let bad_price = r#"[{"event_type":"last_trade_price","asset_id":"1","price":"1.5"},{"event_type":"subscribed"}]"#;
+        let events = parse_frame(bad_price).unwrap();
+        let err = events[0].as_ref().unwrap_err();
+        assert!(format!("{:#}", err).contains("price 1.5 outside 0..=1"));
+        assert!(matches!(events[1], Ok(Event::Subscribed)));
+
+        let too_fine =
+            r#"{"event_type":"tick_size_change","asset_id":"1","new_tick_size":"0.00001"}"#;
+        let err = parse_event(too_fine).unwrap_err();
+        assert!(format!("{:#}", err).contains("invalid price \"0.00001\""));
+
+        let orphan =
+            r#"{"event_type":"price_change","changes":[{"price":"0.5","size":"1","side":"BUY"}]}"#;
+        match parse_event(orphan).unwrap() {
+            Event::PriceChange(change) => assert!(change.entries().is_err()),
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        let future = r#"{"event_type":"best_bid_ask","asset_id":"1"}"#;
+        assert!(matches!(parse_event(future).unwrap(), Event::Unknown));
+
+        let error = r#"{"event_type":"error","message":"invalid asset id"}"#;
+        match parse_event(error).unwrap() {
+            Event::Error(body) => assert!(body.contains("invalid asset id")),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+}
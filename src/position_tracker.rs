@@ -1,13 +1,38 @@
 //! Position tracking and P&L calculation.
 
 use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::lifecycle::OpportunityKey;
+use crate::orders::{Order, OrderAction, OrderEvent, OrderHook};
+use crate::types::{Contracts, Leg, Notional, Side, Venue};
+
+/// Contracts held on each outcome of a market, in Kalshi's terms
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Exposure {
+    pub yes: Contracts,
+    pub no: Contracts,
+}
+
+impl Exposure {
+    /// Contracts on one outcome not offset by the other
+    pub fn unhedged(&self) -> Contracts {
+        self.yes.max(self.no).saturating_sub(self.yes.min(self.no))
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct PositionTracker {
-    /// Market ID -> position size (in contracts)
-    positions: HashMap<String, u16>,
-    /// Total P&L in cents
-    total_pnl: i32,
+    /// Market ID -> position size
+    positions: HashMap<String, Contracts>,
+    /// Market ID -> holdings per outcome
+    exposure: HashMap<String, Exposure>,
+    /// (venue, ticker or token, venue side) -> contracts held
+    holdings: HashMap<(Venue, String, Side), Contracts>,
+    /// P&L of unwinding naked legs, by originating opportunity
+    unwind_pnl: HashMap<OpportunityKey, Notional>,
+    /// Total P&L
+    total_pnl: Notional,
     /// Trade count
     trade_count: u32,
 }
@@ -18,25 +43,144 @@ impl PositionTracker {
     }
 
     /// Check if we can trade (within position limits)
-    pub fn can_trade(&self, market_id: &str, max_size: u16) -> bool {
-        let current = self.positions.get(market_id).copied().unwrap_or(0);
-        current < max_size
+    pub fn can_trade(&self, market_id: &str, max_size: Contracts) -> bool {
+        self.get_position(market_id) < max_size
+    }
+
+    /// Contracts that can still be added to a market's position
+    pub fn remaining(&self, market_id: &str, max_size: Contracts) -> Contracts {
+        max_size.saturating_sub(self.get_position(market_id))
+    }
+
+    /// Record a trade (dry-run or actual) of `quantity` on both legs.
+    ///
+    /// Each leg counts toward the outcome it pays out on, so an inverted
+    /// Polymarket leg offsets the Kalshi leg it was bought against.
+    pub fn record_trade(
+        &mut self,
+        market_id: &str,
+        legs: [&Leg; 2],
+        quantity: Contracts,
+        pnl: Notional,
+    ) {
+        for leg in legs {
+            self.record_fill(market_id, leg, quantity);
+        }
+        self.record_execution(market_id, quantity, pnl);
     }
 
-    /// Record a trade (dry-run or actual)
-    pub fn record_trade(&mut self, market_id: &str, profit_cents: i16) {
-        *self.positions.entry(market_id.to_string()).or_insert(0) += 1;
-        self.total_pnl += profit_cents as i32;
+    /// Record `quantity` contracts bought on one leg of a market
+    pub fn record_fill(&mut self, market_id: &str, leg: &Leg, quantity: Contracts) {
+        let exposure = self.exposure.entry(market_id.to_string()).or_default();
+        let held = match leg.outcome {
+            Side::Yes => &mut exposure.yes,
+            Side::No => &mut exposure.no,
+        };
+        *held = held.saturating_add(quantity);
+        self.add_holdings(&[leg], quantity);
+    }
+
+    /// Record `quantity` contracts of one leg sold back
+    pub fn record_sale(&mut self, market_id: &str, leg: &Leg, quantity: Contracts) {
+        let exposure = self.exposure.entry(market_id.to_string()).or_default();
+        let held = match leg.outcome {
+            Side::Yes => &mut exposure.yes,
+            Side::No => &mut exposure.no,
+        };
+        *held = held.saturating_sub(quantity);
+        let holding = self
+            .holdings
+            .entry((leg.venue, leg.instrument.clone(), leg.side))
+            .or_default();
+        *holding = holding.saturating_sub(quantity);
+    }
+
+    /// Record an executed opportunity whose fills were recorded as they
+    /// arrived: `quantity` counts toward the market's position limit
+    pub fn record_execution(&mut self, market_id: &str, quantity: Contracts, pnl: Notional) {
+        let position = self
+            .positions
+            .entry(market_id.to_string())
+            .or_insert(Contracts::ZERO);
+        *position = position.saturating_add(quantity);
+        self.total_pnl = self.total_pnl.saturating_add(pnl);
         self.trade_count += 1;
     }
 
+    /// Record a multi-outcome basket of `quantity` on every leg.
+    ///
+    /// The position counts against the event; no single market is hedged
+    /// on its own, so per-market exposure is left alone.
+    pub fn record_basket(
+        &mut self,
+        event_id: &str,
+        legs: &[&Leg],
+        quantity: Contracts,
+        pnl: Notional,
+    ) {
+        let position = self
+            .positions
+            .entry(event_id.to_string())
+            .or_insert(Contracts::ZERO);
+        *position = position.saturating_add(quantity);
+        self.add_holdings(legs, quantity);
+        self.total_pnl = self.total_pnl.saturating_add(pnl);
+        self.trade_count += 1;
+    }
+
+    fn add_holdings(&mut self, legs: &[&Leg], quantity: Contracts) {
+        for leg in legs {
+            let holding = self
+                .holdings
+                .entry((leg.venue, leg.instrument.clone(), leg.side))
+                .or_default();
+            *holding = holding.saturating_add(quantity);
+        }
+    }
+
+    /// Record what unwinding an opportunity's naked leg made or lost
+    pub fn record_unwind(&mut self, opportunity: &OpportunityKey, pnl: Notional) {
+        let total = self.unwind_pnl.entry(opportunity.clone()).or_default();
+        *total = total.saturating_add(pnl);
+        self.total_pnl = self.total_pnl.saturating_add(pnl);
+    }
+
+    /// Unwind P&L recorded against an opportunity
+    pub fn unwind_pnl(&self, opportunity: &OpportunityKey) -> Notional {
+        self.unwind_pnl
+            .get(opportunity)
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Get current position for a market
-    pub fn get_position(&self, market_id: &str) -> u16 {
-        self.positions.get(market_id).copied().unwrap_or(0)
+    pub fn get_position(&self, market_id: &str) -> Contracts {
+        self.positions.get(market_id).copied().unwrap_or_default()
+    }
+
+    /// Holdings per outcome for a market
+    pub fn exposure(&self, market_id: &str) -> Exposure {
+        self.exposure.get(market_id).copied().unwrap_or_default()
+    }
+
+    /// Contracts held on one venue instrument and side
+    pub fn holding(&self, venue: Venue, instrument: &str, side: Side) -> Contracts {
+        self.holdings
+            .get(&(venue, instrument.to_string(), side))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Markets with more contracts on one outcome than the other
+    pub fn unhedged_markets(&self) -> usize {
+        self.exposure
+            .values()
+            .filter(|e| !e.unhedged().is_zero())
+            .count()
     }
 
-    /// Get total P&L in cents
-    pub fn total_pnl(&self) -> i32 {
+    /// Get total P&L
+    pub fn total_pnl(&self) -> Notional {
         self.total_pnl
     }
 
@@ -48,10 +192,49 @@ impl PositionTracker {
     /// Get P&L summary
     pub fn summary(&self) -> String {
         format!(
-            "Trades: {} | P&L: ${:.2} | Positions: {}",
+            "Trades: {} | P&L: {} | Positions: {} | Unhedged: {}",
             self.trade_count,
-            self.total_pnl as f64 / 100.0,
-            self.positions.len()
+            self.total_pnl,
+            self.positions.len(),
+            self.unhedged_markets()
         )
     }
 }
+
+/// Live fills update positions as the venues report them
+impl OrderHook for RwLock<PositionTracker> {
+    fn on_order_event(&self, order: &Order, event: &OrderEvent) {
+        if let OrderEvent::Fill { quantity, .. } = event {
+            let mut tracker = self.write().unwrap();
+            match order.action {
+                OrderAction::Buy => tracker.record_fill(&order.market_id, &order.leg, *quantity),
+                OrderAction::Sell => tracker.record_sale(&order.market_id, &order.leg, *quantity),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::get_hardcoded_markets;
+    use crate::types::Orientation;
+
+    #[test]
+    fn test_inverted_legs_offset_each_other() {
+        let mut pair = get_hardcoded_markets().remove(0);
+        pair.orientation = Orientation::Inverted;
+        let mut tracker = PositionTracker::new();
+
+        // Kalshi YES hedged by Polymarket's NO token, which is Kalshi's NO outcome
+        let yes = pair.leg(Venue::Kalshi, Side::Yes);
+        let no = pair.leg(Venue::Polymarket, Side::No);
+        assert_eq!(no.side, Side::Yes);
+        tracker.record_trade(&pair.id, [&yes, &no], Contracts(5), Notional::ZERO);
+
+        assert_eq!(tracker.exposure(&pair.id).unhedged(), Contracts::ZERO);
+        let held = tracker.holding(Venue::Polymarket, &pair.poly_yes_token, Side::Yes);
+        assert_eq!(held, Contracts(5));
+        assert_eq!(tracker.unhedged_markets(), 0);
+    }
+}
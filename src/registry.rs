@@ -0,0 +1,215 @@
+//! Tracked market registry and venue routing index.
+//!
+//! The registry owns every `MarketState`. Whenever a market is added or
+//! removed it rebuilds an immutable [`RoutingIndex`] mapping venue
+//! identifiers straight to market state, so feed adapters resolve incoming
+//! messages with a single hash lookup and never take the market map lock.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use crate::types::{EventGroup, MarketPair, MarketState, Side};
+
+/// Venue identifier -> market lookup tables
+#[derive(Debug, Default)]
+pub struct RoutingIndex {
+    kalshi: HashMap<String, Arc<MarketState>>,
+    poly: HashMap<String, (Arc<MarketState>, Side)>,
+}
+
+impl RoutingIndex {
+    fn build(markets: &HashMap<String, Arc<MarketState>>) -> Self {
+        let mut index = Self::default();
+        for market in markets.values() {
+            index
+                .kalshi
+                .insert(market.pair.kalshi_ticker.clone(), market.clone());
+            // Tokens route to the Kalshi-frame side they pay out on
+            for side in [Side::Yes, Side::No] {
+                index.poly.insert(
+                    market.pair.poly_token(side).to_string(),
+                    (market.clone(), side),
+                );
+            }
+        }
+        index
+    }
+
+    /// Market for a Kalshi ticker
+    pub fn kalshi(&self, ticker: &str) -> Option<&Arc<MarketState>> {
+        self.kalshi.get(ticker)
+    }
+
+    /// Market and side for a Polymarket token id
+    pub fn poly(&self, token_id: &str) -> Option<&(Arc<MarketState>, Side)> {
+        self.poly.get(token_id)
+    }
+
+    /// Every routed Kalshi ticker
+    pub fn kalshi_tickers(&self) -> impl Iterator<Item = &String> {
+        self.kalshi.keys()
+    }
+
+    /// Every routed Polymarket token id
+    pub fn poly_tokens(&self) -> impl Iterator<Item = &String> {
+        self.poly.keys()
+    }
+
+    /// Number of markets routed
+    pub fn market_count(&self) -> usize {
+        self.kalshi.len()
+    }
+}
+
+/// All tracked markets plus the routing index derived from them
+#[derive(Debug, Default)]
+pub struct MarketRegistry {
+    markets: RwLock<HashMap<String, Arc<MarketState>>>,
+    routes: RwLock<Arc<RoutingIndex>>,
+    /// Multi-outcome events over tracked markets, by event id
+    events: RwLock<HashMap<String, Arc<EventGroup>>>,
+    /// Bumped on every add/remove so feeds know to resubscribe
+    version: AtomicU64,
+}
+
+impl MarketRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking `pair`, replacing any market with the same id
+    pub fn add(&self, pair: MarketPair) -> Arc<MarketState> {
+        let state = Arc::new(MarketState::new(pair));
+        let mut markets = self.markets.write().unwrap();
+        markets.insert(state.pair.id.clone(), state.clone());
+        self.rebuild(&markets);
+        state
+    }
+
+    /// Stop tracking a market
+    pub fn remove(&self, market_id: &str) -> Option<Arc<MarketState>> {
+        let mut markets = self.markets.write().unwrap();
+        let removed = markets.remove(market_id);
+        if removed.is_some() {
+            self.rebuild(&markets);
+        }
+        removed
+    }
+
+    fn rebuild(&self, markets: &HashMap<String, Arc<MarketState>>) {
+        *self.routes.write().unwrap() = Arc::new(RoutingIndex::build(markets));
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    pub fn get(&self, market_id: &str) -> Option<Arc<MarketState>> {
+        self.markets.read().unwrap().get(market_id).cloned()
+    }
+
+    /// Snapshot of every tracked market
+    pub fn all(&self) -> Vec<Arc<MarketState>> {
+        self.markets.read().unwrap().values().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.markets.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Current routing index (cheap to clone, immutable once built)
+    pub fn routes(&self) -> Arc<RoutingIndex> {
+        self.routes.read().unwrap().clone()
+    }
+
+    /// Track a multi-outcome event over markets added with [`Self::add`]
+    pub fn add_event(&self, group: EventGroup) -> Arc<EventGroup> {
+        let group = Arc::new(group);
+        self.events
+            .write()
+            .unwrap()
+            .insert(group.id.clone(), group.clone());
+        group
+    }
+
+    /// Every tracked event
+    pub fn events(&self) -> Vec<Arc<EventGroup>> {
+        self.events.read().unwrap().values().cloned().collect()
+    }
+
+    /// Events that include `market_id` as an outcome
+    pub fn events_for(&self, market_id: &str) -> Vec<Arc<EventGroup>> {
+        self.events
+            .read()
+            .unwrap()
+            .values()
+            .filter(|group| group.market_ids.iter().any(|id| id == market_id))
+            .cloned()
+            .collect()
+    }
+
+    /// State for every outcome of `group`, or `None` if one isn't tracked
+    pub fn event_markets(&self, group: &EventGroup) -> Option<Vec<Arc<MarketState>>> {
+        let markets = self.markets.read().unwrap();
+        group
+            .market_ids
+            .iter()
+            .map(|id| markets.get(id).cloned())
+            .collect()
+    }
+
+    /// Changes whenever markets are added or removed
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::get_hardcoded_markets;
+    use crate::types::{Orientation, Venue};
+
+    #[test]
+    fn test_routes_follow_add_and_remove() {
+        let registry = MarketRegistry::new();
+        let pairs = get_hardcoded_markets();
+        for pair in pairs.clone() {
+            registry.add(pair);
+        }
+
+        let routes = registry.routes();
+        assert_eq!(routes.market_count(), pairs.len());
+        let (market, side) = routes.poly(&pairs[1].poly_no_token).unwrap();
+        assert_eq!(market.pair.id, pairs[1].id);
+        assert_eq!(*side, Side::No);
+
+        let version = registry.version();
+        registry.remove(&pairs[1].id);
+        assert!(registry.version() > version);
+        assert!(registry.routes().kalshi(&pairs[1].kalshi_ticker).is_none());
+        // Snapshots handed out earlier stay valid
+        assert!(routes.kalshi(&pairs[1].kalshi_ticker).is_some());
+    }
+
+    #[test]
+    fn test_inverted_pair_routes_tokens_to_opposite_side() {
+        let mut pair = get_hardcoded_markets().remove(0);
+        pair.orientation = Orientation::Inverted;
+        let registry = MarketRegistry::new();
+        registry.add(pair.clone());
+
+        // Polymarket's YES token feeds the market's NO book
+        let routes = registry.routes();
+        assert_eq!(routes.poly(&pair.poly_yes_token).unwrap().1, Side::No);
+        assert_eq!(routes.poly(&pair.poly_no_token).unwrap().1, Side::Yes);
+
+        let leg = pair.leg(Venue::Polymarket, Side::Yes);
+        assert_eq!(
+            (leg.instrument.as_str(), leg.side),
+            (pair.poly_no_token.as_str(), Side::No)
+        );
+    }
+}
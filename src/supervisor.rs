@@ -0,0 +1,372 @@
+//! Feed connection supervision: reconnect backoff and per-venue health.
+//!
+//! [`supervise`] keeps one venue's feed running forever. Every failure is
+//! classified so rate limits back off harder than network blips, and the
+//! venue's books are cleared whenever the connection drops so detection never
+//! sees pre-disconnect prices.
+
+use anyhow::Result;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite;
+use tracing::{error, info, warn};
+
+use crate::config::{
+    WS_RATE_LIMIT_DELAY_SECS, WS_RECONNECT_ALERT_ATTEMPTS, WS_RECONNECT_BASE_DELAY_MS,
+    WS_RECONNECT_MAX_DELAY_SECS,
+};
+use crate::feed::{run_feed, BookUpdate, FeedClient, LivenessFailure};
+use crate::registry::MarketRegistry;
+use crate::types::Venue;
+
+/// Connection state of one venue feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// First connection attempt in progress
+    Connecting,
+    /// Subscribed and receiving data
+    Live,
+    /// Connection lost, reconnecting
+    Degraded,
+    /// Reconnect attempts exhausted the alert threshold (still retrying)
+    Down,
+}
+
+impl std::fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionState::Connecting => write!(f, "connecting"),
+            ConnectionState::Live => write!(f, "live"),
+            ConnectionState::Degraded => write!(f, "degraded"),
+            ConnectionState::Down => write!(f, "down"),
+        }
+    }
+}
+
+/// Shared connection state for every venue
+#[derive(Debug, Default)]
+pub struct ConnectionHealth {
+    states: RwLock<HashMap<Venue, ConnectionState>>,
+    /// Liveness failures raised per venue
+    liveness_failures: RwLock<HashMap<Venue, u64>>,
+    /// Frames rejected by the venue adapter's decoder
+    malformed_frames: RwLock<HashMap<Venue, u64>>,
+    /// When each venue's connection last delivered a frame
+    last_frames: RwLock<HashMap<Venue, Instant>>,
+}
+
+/// When each venue's connection last delivered a frame, as of one read
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FeedLiveness {
+    pub kalshi: Option<Instant>,
+    pub polymarket: Option<Instant>,
+}
+
+impl FeedLiveness {
+    pub fn last_frame(&self, venue: Venue) -> Option<Instant> {
+        match venue {
+            Venue::Kalshi => self.kalshi,
+            Venue::Polymarket => self.polymarket,
+        }
+    }
+}
+
+impl ConnectionHealth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current state of `venue` (`Connecting` until first reported)
+    pub fn get(&self, venue: Venue) -> ConnectionState {
+        self.states
+            .read()
+            .unwrap()
+            .get(&venue)
+            .copied()
+            .unwrap_or(ConnectionState::Connecting)
+    }
+
+    pub fn set(&self, venue: Venue, state: ConnectionState) {
+        let previous = self.states.write().unwrap().insert(venue, state);
+        if previous != Some(state) {
+            info!("[{}] Connection state: {}", venue.tag(), state);
+        }
+    }
+
+    /// Raise a health event for a connection the liveness checks gave up on
+    pub fn raise(&self, venue: Venue, failure: &LivenessFailure) {
+        *self
+            .liveness_failures
+            .write()
+            .unwrap()
+            .entry(venue)
+            .or_insert(0) += 1;
+        warn!(
+            "⚠️  [{}] Health event: {} - forcing reconnect",
+            venue.tag(),
+            failure
+        );
+        self.set(venue, ConnectionState::Degraded);
+    }
+
+    /// Liveness failures raised for `venue` so far
+    pub fn liveness_failures(&self, venue: Venue) -> u64 {
+        self.liveness_failures
+            .read()
+            .unwrap()
+            .get(&venue)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Count a frame the adapter could not decode
+    pub fn record_malformed(&self, venue: Venue) {
+        *self
+            .malformed_frames
+            .write()
+            .unwrap()
+            .entry(venue)
+            .or_insert(0) += 1;
+    }
+
+    /// Frames rejected for `venue` so far
+    pub fn malformed_frames(&self, venue: Venue) -> u64 {
+        self.malformed_frames
+            .read()
+            .unwrap()
+            .get(&venue)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Note a frame (data or heartbeat) received on `venue`'s connection
+    pub fn record_frame(&self, venue: Venue, at: Instant) {
+        self.last_frames.write().unwrap().insert(venue, at);
+    }
+
+    /// Last frame received on each venue's connection
+    pub fn liveness(&self) -> FeedLiveness {
+        let last_frames = self.last_frames.read().unwrap();
+        FeedLiveness {
+            kalshi: last_frames.get(&Venue::Kalshi).copied(),
+            polymarket: last_frames.get(&Venue::Polymarket).copied(),
+        }
+    }
+
+    /// One-line summary for the heartbeat log
+    pub fn summary(&self) -> String {
+        [Venue::Kalshi, Venue::Polymarket]
+            .iter()
+            .map(|v| {
+                format!(
+                    "{}: {} ({} stalls, {} malformed)",
+                    v,
+                    self.get(*v),
+                    self.liveness_failures(*v),
+                    self.malformed_frames(*v)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+/// Why a feed connection ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disconnect {
+    /// Server closed the connection or the stream ended
+    Closed,
+    /// Server rejected the handshake with HTTP 429
+    RateLimited { retry_after: Option<Duration> },
+    /// DNS, TCP, TLS or other transport failure
+    Network,
+    /// Connection stayed open but stopped delivering data
+    Idle,
+    /// Any other error (handshake rejected, protocol violation, ...)
+    Other,
+}
+
+impl Disconnect {
+    /// Classify the result of a `run_feed` call
+    pub fn classify(result: &Result<()>) -> Self {
+        let err = match result {
+            Ok(()) => return Disconnect::Closed,
+            Err(e) => e,
+        };
+
+        if err.downcast_ref::<LivenessFailure>().is_some() {
+            return Disconnect::Idle;
+        }
+
+        match err.downcast_ref::<tungstenite::Error>() {
+            Some(tungstenite::Error::Http(response)) if response.status().as_u16() == 429 => {
+                let retry_after = response
+                    .headers()
+                    .get("retry-after")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.trim().parse::<u64>().ok())
+                    .map(Duration::from_secs);
+                Disconnect::RateLimited { retry_after }
+            }
+            Some(tungstenite::Error::Io(_)) | Some(tungstenite::Error::Tls(_)) => {
+                Disconnect::Network
+            }
+            Some(tungstenite::Error::ConnectionClosed) => Disconnect::Closed,
+            _ if err.downcast_ref::<std::io::Error>().is_some() => Disconnect::Network,
+            _ => Disconnect::Other,
+        }
+    }
+}
+
+/// Exponential backoff with full jitter
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl Backoff {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max,
+            attempt: 0,
+        }
+    }
+
+    /// Consecutive failures since the last reset
+    pub fn attempts(&self) -> u32 {
+        self.attempt
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// Delay before the next attempt: uniform in [0, min(max, base × 2^n)]
+    pub fn next_delay(&mut self) -> Duration {
+        let ceiling = self
+            .base
+            .saturating_mul(1u32 << self.attempt.min(16))
+            .min(self.max);
+        self.attempt = self.attempt.saturating_add(1);
+        ceiling.mul_f64(jitter_fraction())
+    }
+}
+
+/// Random fraction in [0, 1) from the std hasher's per-instance random keys
+fn jitter_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Keep `client` connected forever, reconnecting with backoff
+pub async fn supervise<C: FeedClient>(
+    mut client: C,
+    registry: Arc<MarketRegistry>,
+    health: Arc<ConnectionHealth>,
+    updates: mpsc::UnboundedSender<BookUpdate>,
+) {
+    let venue = client.venue();
+    let tag = venue.tag();
+    let mut backoff = Backoff::new(
+        Duration::from_millis(WS_RECONNECT_BASE_DELAY_MS),
+        Duration::from_secs(WS_RECONNECT_MAX_DELAY_SECS),
+    );
+
+    loop {
+        let result = run_feed(&mut client, &registry, &health, &updates).await;
+        let was_live = health.get(venue) == ConnectionState::Live;
+
+        // Prices from the dropped connection must not be traded on
+        clear_books(venue, &registry);
+
+        // A connection that made it to Live starts the backoff over. Liveness
+        // failures already marked the venue Degraded, so repeated stalls
+        // keep escalating toward the alert.
+        if was_live {
+            backoff.reset();
+        }
+
+        let reason = Disconnect::classify(&result);
+        let mut delay = backoff.next_delay();
+        match (&result, reason) {
+            (_, Disconnect::RateLimited { retry_after }) => {
+                let floor = retry_after.unwrap_or(Duration::from_secs(WS_RATE_LIMIT_DELAY_SECS));
+                delay = delay.max(floor);
+                warn!("[{}] Rate limited by server", tag);
+            }
+            (Err(e), _) => error!("[{}] WebSocket error ({:?}): {:#}", tag, reason, e),
+            (Ok(()), _) => warn!("[{}] Connection closed", tag),
+        }
+
+        if backoff.attempts() >= WS_RECONNECT_ALERT_ATTEMPTS {
+            if health.get(venue) != ConnectionState::Down {
+                error!(
+                    "🚨 [{}] ALERT: {} consecutive reconnect failures - feed is DOWN",
+                    tag,
+                    backoff.attempts()
+                );
+            }
+            health.set(venue, ConnectionState::Down);
+        } else {
+            health.set(venue, ConnectionState::Degraded);
+        }
+
+        info!(
+            "[{}] Reconnecting in {:.1}s (attempt {})",
+            tag,
+            delay.as_secs_f64(),
+            backoff.attempts()
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Reset every book for `venue` to empty
+fn clear_books(venue: Venue, registry: &MarketRegistry) {
+    for market in registry.all() {
+        market.book(venue).clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let mut backoff = Backoff::new(Duration::from_millis(100), Duration::from_secs(1));
+        for attempt in 0..10u32 {
+            let ceiling = Duration::from_millis(100 * (1 << attempt)).min(Duration::from_secs(1));
+            assert!(backoff.next_delay() <= ceiling);
+        }
+        assert_eq!(backoff.attempts(), 10);
+        backoff.reset();
+        assert!(backoff.next_delay() <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_classify_rate_limit() {
+        let response = tungstenite::http::Response::builder()
+            .status(429)
+            .header("Retry-After", "12")
+            .body(None)
+            .unwrap();
+        let result: Result<()> = Err(
+            anyhow::Error::new(tungstenite::Error::Http(response)).context("Failed to connect")
+        );
+        assert_eq!(
+            Disconnect::classify(&result),
+            Disconnect::RateLimited {
+                retry_after: Some(Duration::from_secs(12))
+            }
+        );
+        assert_eq!(Disconnect::classify(&Ok(())), Disconnect::Closed);
+    }
+}
@@ -1,16 +1,270 @@
 //! Core type definitions for the arbitrage trading system.
 
+use arc_swap::{ArcSwap, Guard};
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-/// Price in cents (1-99 for $0.01-$0.99), 0 means no price available
-pub type PriceCents = u16;
+use crate::edge::{time_to_settlement, EdgeReturn, MinEdge, MinEdgeDefaults};
+use crate::fees::PairFees;
+use crate::lines::{Line, MiddleKind, Scenario};
 
-/// Size in cents (dollar amount × 100)
-pub type SizeCents = u16;
+/// Fixed-point price in units of $0.0001, so $1.00 = `Price(10_000)`.
+///
+/// Fine enough for Polymarket's 0.001 (and 0.0001) ticks; Kalshi prices are
+/// whole cents. `Price::ZERO` means no price available.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct Price(pub u32);
+
+impl Price {
+    /// Units per dollar
+    pub const SCALE: u32 = 10_000;
+    pub const ZERO: Price = Price(0);
+    pub const CENT: Price = Price(Self::SCALE / 100);
+    /// Payout of a winning contract
+    pub const ONE: Price = Price(Self::SCALE);
+
+    pub const fn from_cents(cents: u32) -> Self {
+        Price(cents * (Self::SCALE / 100))
+    }
+
+    /// Nearest price to a dollar amount; `None` if negative or not finite
+    pub fn from_dollars(dollars: f64) -> Option<Self> {
+        let units = (dollars * Self::SCALE as f64).round();
+        if !units.is_finite() || units < 0.0 || units > u32::MAX as f64 {
+            return None;
+        }
+        Some(Price(units as u32))
+    }
+
+    /// Exact price from a decimal string ("0.523"); `None` if malformed or
+    /// finer than the fixed-point scale
+    pub fn parse_decimal(s: &str) -> Option<Self> {
+        let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+        if whole.is_empty() && frac.is_empty() {
+            return None;
+        }
+        let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if !all_digits(whole) || !all_digits(frac) {
+            return None;
+        }
+        let frac = frac.trim_end_matches('0');
+        if frac.len() > 4 {
+            return None;
+        }
+
+        let whole: u32 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().ok()?
+        };
+        let frac_units: u32 = if frac.is_empty() {
+            0
+        } else {
+            frac.parse::<u32>().ok()? * 10u32.pow(4 - frac.len() as u32)
+        };
+        whole
+            .checked_mul(Self::SCALE)?
+            .checked_add(frac_units)
+            .map(Price)
+    }
+
+    pub fn units(self) -> u32 {
+        self.0
+    }
+
+    pub fn dollars(self) -> f64 {
+        self.0 as f64 / Self::SCALE as f64
+    }
+
+    pub fn cents(self) -> f64 {
+        self.0 as f64 / Self::CENT.0 as f64
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Price of the opposite outcome (`ONE - self`)
+    pub fn complement(self) -> Option<Price> {
+        Self::ONE.checked_sub(self)
+    }
+
+    pub fn checked_sub(self, other: Price) -> Option<Price> {
+        self.0.checked_sub(other.0).map(Price)
+    }
+
+    pub fn saturating_sub(self, other: Price) -> Price {
+        Price(self.0.saturating_sub(other.0))
+    }
+
+    /// Whether the price is a whole number of `tick`s
+    pub fn is_on_tick(self, tick: Price) -> bool {
+        tick.0 == 0 || self.0.is_multiple_of(tick.0)
+    }
+
+    /// Smallest multiple of `tick` at or above this price
+    pub fn ceil_to(self, tick: Price) -> Price {
+        if tick.0 == 0 {
+            return self;
+        }
+        Price(self.0.div_ceil(tick.0) * tick.0)
+    }
+
+    /// Largest multiple of `tick` at or below this price
+    pub fn floor_to(self, tick: Price) -> Price {
+        if tick.0 == 0 {
+            return self;
+        }
+        Price(self.0 / tick.0 * tick.0)
+    }
+}
+
+impl std::ops::Add for Price {
+    type Output = Price;
+
+    fn add(self, other: Price) -> Price {
+        Price(self.0 + other.0)
+    }
+}
+
+impl std::fmt::Display for Price {
+    /// Cents, with sub-cent digits only when present: `42¢`, `42.5¢`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cent = Self::CENT.0;
+        let (whole, frac) = (self.0 / cent, self.0 % cent);
+        if frac == 0 {
+            write!(f, "{}¢", whole)
+        } else {
+            let digits = format!("{:02}", frac);
+            write!(f, "{}.{}¢", whole, digits.trim_end_matches('0'))
+        }
+    }
+}
+
+/// Quantity in whole contracts (Polymarket shares are truncated)
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct Contracts(pub u64);
+
+impl Contracts {
+    pub const ZERO: Contracts = Contracts(0);
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whole contracts in a fractional share size; `None` if the size is
+    /// negative, not finite, or too large
+    pub fn from_shares(shares: f64) -> Option<Self> {
+        if !shares.is_finite() || shares < 0.0 || shares >= u64::MAX as f64 {
+            return None;
+        }
+        Some(Contracts(shares.floor() as u64))
+    }
+
+    pub fn checked_add(self, other: Contracts) -> Option<Contracts> {
+        self.0.checked_add(other.0).map(Contracts)
+    }
+
+    pub fn checked_sub(self, other: Contracts) -> Option<Contracts> {
+        self.0.checked_sub(other.0).map(Contracts)
+    }
+
+    pub fn saturating_add(self, other: Contracts) -> Contracts {
+        Contracts(self.0.saturating_add(other.0))
+    }
+
+    pub fn saturating_sub(self, other: Contracts) -> Contracts {
+        Contracts(self.0.saturating_sub(other.0))
+    }
+
+    /// Cost of this many contracts at `price` each
+    pub fn notional(self, price: Price) -> Option<Notional> {
+        i64::try_from(self.0)
+            .ok()?
+            .checked_mul(price.0 as i64)
+            .map(Notional)
+    }
+}
+
+impl std::fmt::Display for Contracts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Money amount in `Price` units of $0.0001 (signed, so it can hold P&L)
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct Notional(pub i64);
+
+impl Notional {
+    pub const ZERO: Notional = Notional(0);
+
+    pub fn from_cents(cents: i64) -> Self {
+        Notional(cents.saturating_mul(Price::CENT.0 as i64))
+    }
+
+    pub fn cents(self) -> f64 {
+        self.0 as f64 / Price::CENT.0 as f64
+    }
+
+    pub fn dollars(self) -> f64 {
+        self.0 as f64 / Price::SCALE as f64
+    }
+
+    pub fn checked_add(self, other: Notional) -> Option<Notional> {
+        self.0.checked_add(other.0).map(Notional)
+    }
+
+    pub fn checked_sub(self, other: Notional) -> Option<Notional> {
+        self.0.checked_sub(other.0).map(Notional)
+    }
+
+    pub fn saturating_add(self, other: Notional) -> Notional {
+        Notional(self.0.saturating_add(other.0))
+    }
+
+    pub fn saturating_sub(self, other: Notional) -> Notional {
+        Notional(self.0.saturating_sub(other.0))
+    }
+}
+
+impl std::ops::Neg for Notional {
+    type Output = Notional;
+
+    fn neg(self) -> Notional {
+        Notional(self.0.saturating_neg())
+    }
+}
+
+impl From<Price> for Notional {
+    fn from(price: Price) -> Notional {
+        Notional(price.units() as i64)
+    }
+}
+
+impl std::fmt::Display for Notional {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0 < 0 {
+            write!(f, "-${:.2}", -self.dollars())
+        } else {
+            write!(f, "${:.2}", self.dollars())
+        }
+    }
+}
 
 /// Sentinel value for no price
-pub const NO_PRICE: PriceCents = 0;
+pub const NO_PRICE: Price = Price::ZERO;
 
 /// Market category
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -18,6 +272,8 @@ pub enum MarketType {
     Moneyline,
     Spread,
     Total,
+    /// One outcome of a multi-outcome event (see [`EventGroup`])
+    Outcome,
 }
 
 impl std::fmt::Display for MarketType {
@@ -26,6 +282,79 @@ impl std::fmt::Display for MarketType {
             MarketType::Moneyline => write!(f, "moneyline"),
             MarketType::Spread => write!(f, "spread"),
             MarketType::Total => write!(f, "total"),
+            MarketType::Outcome => write!(f, "outcome"),
+        }
+    }
+}
+
+/// Trading venue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Venue {
+    Kalshi,
+    Polymarket,
+}
+
+impl Venue {
+    /// Log prefix tag, e.g. `[KALSHI]`
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Venue::Kalshi => "KALSHI",
+            Venue::Polymarket => "POLYMARKET",
+        }
+    }
+}
+
+impl std::fmt::Display for Venue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Venue::Kalshi => write!(f, "Kalshi"),
+            Venue::Polymarket => write!(f, "Polymarket"),
+        }
+    }
+}
+
+/// Contract side of a binary market
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Side {
+    Yes,
+    No,
+}
+
+impl Side {
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Yes => Side::No,
+            Side::No => Side::Yes,
+        }
+    }
+}
+
+impl std::fmt::Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Side::Yes => write!(f, "YES"),
+            Side::No => write!(f, "NO"),
+        }
+    }
+}
+
+/// How a Polymarket market's outcomes line up with the Kalshi ticker's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    /// Polymarket YES pays out when Kalshi YES does
+    #[default]
+    Same,
+    /// Polymarket YES is Kalshi's NO outcome (e.g. the other team)
+    Inverted,
+}
+
+impl Orientation {
+    /// Polymarket side paying out on Kalshi's `side`
+    pub fn apply(self, side: Side) -> Side {
+        match self {
+            Orientation::Same => side,
+            Orientation::Inverted => side.opposite(),
         }
     }
 }
@@ -40,37 +369,283 @@ pub struct MarketPair {
     pub poly_slug: String,
     pub poly_yes_token: String,
     pub poly_no_token: String,
+    /// Whether the Polymarket outcomes are flipped relative to Kalshi's
+    #[serde(default)]
+    pub orientation: Orientation,
+    /// Kalshi's spread/total line; YES pays above it
+    #[serde(default)]
+    pub kalshi_line: Option<Line>,
+    /// Polymarket's line for the same YES outcome
+    #[serde(default)]
+    pub poly_line: Option<Line>,
+    /// Fee schedule on each venue
+    #[serde(default)]
+    pub fees: PairFees,
+    /// When the market is expected to settle and pay out
+    #[serde(default)]
+    pub settles_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Minimum edge for this market; the market type's default if unset
+    #[serde(default)]
+    pub min_edge: Option<MinEdge>,
+}
+
+impl MarketPair {
+    /// Polymarket token paying out on Kalshi's `side`.
+    ///
+    /// Market state, detection and positions all use Kalshi's outcomes;
+    /// this is the only place Polymarket's own labels are mapped.
+    pub fn poly_token(&self, side: Side) -> &str {
+        match self.orientation.apply(side) {
+            Side::Yes => &self.poly_yes_token,
+            Side::No => &self.poly_no_token,
+        }
+    }
+
+    /// Whether both venues settle on the same line, so cross-venue pairs
+    /// are true arbitrage
+    pub fn lines_match(&self) -> bool {
+        self.kalshi_line == self.poly_line
+    }
+
+    /// Minimum edge an opportunity on this market must clear
+    pub fn edge_threshold(&self, defaults: &MinEdgeDefaults) -> MinEdge {
+        self.min_edge
+            .unwrap_or_else(|| defaults.get(self.market_type))
+    }
+
+    /// Time from `now` until the market settles, if known
+    pub fn settles_in(&self, now: chrono::DateTime<chrono::Utc>) -> Option<std::time::Duration> {
+        time_to_settlement(self.settles_at, now)
+    }
+
+    /// What to buy on `venue` to hold Kalshi's `outcome`
+    pub fn leg(&self, venue: Venue, outcome: Side) -> Leg {
+        match venue {
+            Venue::Kalshi => Leg {
+                venue,
+                instrument: self.kalshi_ticker.clone(),
+                side: outcome,
+                outcome,
+            },
+            Venue::Polymarket => Leg {
+                venue,
+                instrument: self.poly_token(outcome).to_string(),
+                side: self.orientation.apply(outcome),
+                outcome,
+            },
+        }
+    }
+}
+
+/// Mutually exclusive outcomes of one event, one tracked market each
+/// (e.g. every candidate in an election, or Kalshi's and Polymarket's
+/// neg-risk markets for a tournament winner)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventGroup {
+    pub id: String,
+    pub description: String,
+    /// `MarketPair` ids, one per outcome
+    pub market_ids: Vec<String>,
+    /// Whether the outcomes cover every result, so exactly one resolves YES.
+    /// A YES basket is only risk-free when this holds.
+    pub exhaustive: bool,
+}
+
+/// One contract to buy on one venue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leg {
+    pub venue: Venue,
+    /// Kalshi ticker or Polymarket token id
+    pub instrument: String,
+    /// Outcome as the venue labels it
+    pub side: Side,
+    /// Outcome in the pair's (Kalshi) terms
+    pub outcome: Side,
+}
+
+impl std::fmt::Display for Leg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} ({})", self.venue, self.side, self.instrument)
+    }
+}
+
+/// A single price level in an ask ladder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PriceLevel {
+    pub price: Price,
+    pub size: Contracts,
+}
+
+/// When one side of a book last changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookStamp {
+    /// Event time reported by the exchange, when the venue provides one
+    pub exchange_ts: Option<chrono::DateTime<chrono::Utc>>,
+    /// Local time the frame carrying the update was received
+    pub received_at: Instant,
+}
+
+impl BookStamp {
+    /// Time since the update was received locally
+    pub fn age(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.received_at)
+    }
+
+    /// Time between two updates, using exchange time when both sides have it
+    pub fn skew(&self, other: &BookStamp) -> Duration {
+        match (self.exchange_ts, other.exchange_ts) {
+            (Some(a), Some(b)) => (a - b).abs().to_std().unwrap_or_default(),
+            _ => {
+                if self.received_at > other.received_at {
+                    self.received_at - other.received_at
+                } else {
+                    other.received_at - self.received_at
+                }
+            }
+        }
+    }
+
+    /// The stamp as of `live_at`, the last frame received on the book's
+    /// connection. Books are cleared when a connection drops, so a book that
+    /// has not changed since is still current at that time.
+    pub fn confirmed(self, live_at: Option<Instant>) -> BookStamp {
+        match live_at {
+            Some(at) if at > self.received_at => BookStamp {
+                exchange_ts: None,
+                received_at: at,
+            },
+            _ => self,
+        }
+    }
 }
 
 /// Orderbook state for a single platform
+///
+/// `yes_ask`/`no_ask` and their sizes mirror the first level of each ladder.
 #[derive(Debug, Clone, Default)]
 pub struct Orderbook {
-    pub yes_ask: PriceCents,
-    pub no_ask: PriceCents,
-    pub yes_size: SizeCents,
-    pub no_size: SizeCents,
+    pub yes_ask: Price,
+    pub no_ask: Price,
+    pub yes_size: Contracts,
+    pub no_size: Contracts,
+    /// Full YES ask ladder, best (lowest) price first
+    pub yes_asks: Vec<PriceLevel>,
+    /// Full NO ask ladder, best (lowest) price first
+    pub no_asks: Vec<PriceLevel>,
+    /// Last update to the YES side
+    pub yes_stamp: Option<BookStamp>,
+    /// Last update to the NO side
+    pub no_stamp: Option<BookStamp>,
+    /// Minimum price increment, when the venue has reported one
+    pub tick_size: Option<Price>,
+}
+
+impl Orderbook {
+    /// Replace one side's ask ladder and refresh its top-of-book fields
+    pub fn set_asks(&mut self, side: Side, asks: Vec<PriceLevel>) {
+        let best = asks.first().copied().unwrap_or_default();
+        match side {
+            Side::Yes => {
+                self.yes_ask = best.price;
+                self.yes_size = best.size;
+                self.yes_asks = asks;
+            }
+            Side::No => {
+                self.no_ask = best.price;
+                self.no_size = best.size;
+                self.no_asks = asks;
+            }
+        }
+    }
+
+    /// Minimum price increment (one cent unless the venue said otherwise)
+    pub fn tick(&self) -> Price {
+        self.tick_size.unwrap_or(Price::CENT)
+    }
+
+    /// Record when `side` was last updated
+    pub fn set_stamp(&mut self, side: Side, stamp: BookStamp) {
+        match side {
+            Side::Yes => self.yes_stamp = Some(stamp),
+            Side::No => self.no_stamp = Some(stamp),
+        }
+    }
+
+    /// Last update time for `side`
+    pub fn stamp(&self, side: Side) -> Option<BookStamp> {
+        match side {
+            Side::Yes => self.yes_stamp,
+            Side::No => self.no_stamp,
+        }
+    }
+
+    /// Ask ladder for `side`, best price first
+    pub fn asks(&self, side: Side) -> &[PriceLevel] {
+        match side {
+            Side::Yes => &self.yes_asks,
+            Side::No => &self.no_asks,
+        }
+    }
+}
+
+/// Lock-free shared orderbook.
+///
+/// Readers load an immutable snapshot, so every field they see comes from the
+/// same update and they never wait on a writer. Writers copy the current
+/// book, modify the copy and publish it atomically.
+#[derive(Debug, Default)]
+pub struct SharedBook(ArcSwap<Orderbook>);
+
+impl SharedBook {
+    /// Consistent snapshot of the current book
+    pub fn load(&self) -> Guard<Arc<Orderbook>> {
+        self.0.load()
+    }
+
+    /// Publish a modified copy of the current book.
+    /// `f` may run more than once if writers race.
+    pub fn update(&self, mut f: impl FnMut(&mut Orderbook)) {
+        self.0.rcu(|current| {
+            let mut next = Orderbook::clone(current);
+            f(&mut next);
+            next
+        });
+    }
+
+    /// Replace the book with an empty one
+    pub fn clear(&self) {
+        self.0.store(Arc::new(Orderbook::default()));
+    }
 }
 
 /// Market state tracking both platforms
 #[derive(Debug, Clone)]
 pub struct MarketState {
     pub pair: MarketPair,
-    pub kalshi: Arc<RwLock<Orderbook>>,
-    pub poly: Arc<RwLock<Orderbook>>,
+    pub kalshi: Arc<SharedBook>,
+    pub poly: Arc<SharedBook>,
 }
 
 impl MarketState {
     pub fn new(pair: MarketPair) -> Self {
         Self {
             pair,
-            kalshi: Arc::new(RwLock::new(Orderbook::default())),
-            poly: Arc::new(RwLock::new(Orderbook::default())),
+            kalshi: Arc::new(SharedBook::default()),
+            poly: Arc::new(SharedBook::default()),
+        }
+    }
+
+    /// Orderbook for `venue`
+    pub fn book(&self, venue: Venue) -> &Arc<SharedBook> {
+        match venue {
+            Venue::Kalshi => &self.kalshi,
+            Venue::Polymarket => &self.poly,
         }
     }
 }
 
 /// Arbitrage opportunity type
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ArbType {
     /// Buy Polymarket YES + Buy Kalshi NO
     PolyYesKalshiNo,
@@ -82,6 +657,18 @@ pub enum ArbType {
     KalshiOnly,
 }
 
+impl ArbType {
+    /// Venues of the YES and NO legs
+    pub fn venues(&self) -> (Venue, Venue) {
+        match self {
+            ArbType::PolyYesKalshiNo => (Venue::Polymarket, Venue::Kalshi),
+            ArbType::KalshiYesPolyNo => (Venue::Kalshi, Venue::Polymarket),
+            ArbType::PolyOnly => (Venue::Polymarket, Venue::Polymarket),
+            ArbType::KalshiOnly => (Venue::Kalshi, Venue::Kalshi),
+        }
+    }
+}
+
 impl std::fmt::Display for ArbType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -99,35 +686,115 @@ pub struct ArbOpportunity {
     pub market_id: String,
     pub description: String,
     pub arb_type: ArbType,
-    pub yes_price: PriceCents,
-    pub no_price: PriceCents,
-    pub total_cost: PriceCents,
-    pub fee: PriceCents,
-    pub profit: i16,
+    pub yes_price: Price,
+    pub no_price: Price,
+    pub total_cost: Price,
+    pub fee: Price,
+    pub profit: Price,
+    /// Contracts executable on both legs while each pair stays profitable
+    pub quantity: Contracts,
+    /// Volume-weighted YES price over `quantity`, in cents
+    pub yes_vwap: f64,
+    /// Volume-weighted NO price over `quantity`, in cents
+    pub no_vwap: f64,
+    /// Worst YES price within `quantity`
+    pub yes_limit: Price,
+    /// Worst NO price within `quantity`
+    pub no_limit: Price,
+    /// Minimum price increment on the YES leg's book
+    pub yes_tick: Price,
+    /// Minimum price increment on the NO leg's book
+    pub no_tick: Price,
+    /// Contracts offered at `yes_price`
+    pub yes_size: Contracts,
+    /// Contracts offered at `no_price`
+    pub no_size: Contracts,
+    /// Expected profit across `quantity` after fees
+    pub expected_profit: Notional,
+    /// Top-of-book return, plain and annualized to settlement
+    pub edge_return: EdgeReturn,
+    /// Fee schedules the legs were priced with
+    pub fees: PairFees,
+    /// What to buy for the YES outcome
+    pub yes_leg: Leg,
+    /// What to buy for the NO outcome
+    pub no_leg: Leg,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
-/// Calculate Kalshi trading fee in cents
-/// Formula: ceil(0.07 × P × (1-P)) in cents
-#[inline]
-pub fn kalshi_fee_cents(price_cents: PriceCents) -> PriceCents {
-    if price_cents == 0 || price_cents >= 100 {
-        return 0;
-    }
-    let p = price_cents as f64 / 100.0;
-    ((0.07 * p * (1.0 - p) * 100.0).ceil() as u16).max(1)
+/// One outcome's leg of a basket
+#[derive(Debug, Clone)]
+pub struct BasketLeg {
+    pub market_id: String,
+    pub leg: Leg,
+    pub price: Price,
+    /// Fee for the basket's whole quantity on this leg
+    pub fee: Notional,
+}
+
+/// YES or NO on every outcome of an event for less than the basket pays
+#[derive(Debug, Clone)]
+pub struct BasketOpportunity {
+    pub event_id: String,
+    pub description: String,
+    /// YES basket pays $1.00; NO basket pays $(N - 1)
+    pub side: Side,
+    pub legs: Vec<BasketLeg>,
+    /// Baskets executable at the top of every leg's book
+    pub quantity: Contracts,
+    /// Everything paid for `quantity` baskets, fees included
+    pub cost: Notional,
+    /// What `quantity` baskets pay out
+    pub payout: Notional,
+    pub expected_profit: Notional,
+    /// Return per basket, plain and annualized to the last leg's settlement
+    pub edge_return: EdgeReturn,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// Cross-venue YES + NO on different lines: not risk-free, so every
+/// outcome is listed
+#[derive(Debug, Clone)]
+pub struct MiddleOpportunity {
+    pub market_id: String,
+    pub description: String,
+    pub arb_type: ArbType,
+    pub kind: MiddleKind,
+    pub yes_leg: Leg,
+    pub no_leg: Leg,
+    pub yes_line: Line,
+    pub no_line: Line,
+    pub yes_price: Price,
+    pub no_price: Price,
+    pub fee: Price,
+    pub total_cost: Price,
+    /// Contracts available at the top of both books
+    pub quantity: Contracts,
+    pub scenarios: Vec<Scenario>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
-/// Convert f64 price (0.01-0.99) to PriceCents (1-99)
-#[inline]
-pub fn price_to_cents(price: f64) -> PriceCents {
-    ((price * 100.0).round() as u16).clamp(0, 99)
+/// Anything detection hands to execution
+#[derive(Debug, Clone)]
+pub enum Opportunity {
+    /// YES + NO on one binary market
+    Pair(ArbOpportunity),
+    /// Every outcome of an event
+    Basket(BasketOpportunity),
+    /// Mismatched lines; reported, never executed
+    Middle(MiddleOpportunity),
 }
 
-/// Convert PriceCents to f64
-#[inline]
-pub fn cents_to_price(cents: PriceCents) -> f64 {
-    cents as f64 / 100.0
+impl Opportunity {
+    /// Ranking key, highest first: annualized return (see [`EdgeReturn::rank`]).
+    /// Middles carry no risk-free return and rank last.
+    pub fn rank(&self) -> i64 {
+        match self {
+            Opportunity::Pair(arb) => arb.edge_return.rank(),
+            Opportunity::Basket(basket) => basket.edge_return.rank(),
+            Opportunity::Middle(_) => i64::MIN,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -135,21 +802,46 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_kalshi_fee_cents() {
-        // At 50 cents: ceil(7 * 50 * 50 / 10000) = ceil(1.75) = 2
-        assert_eq!(kalshi_fee_cents(50), 2);
-        // At 10 cents: ceil(7 * 10 * 90 / 10000) = ceil(0.63) = 1
-        assert_eq!(kalshi_fee_cents(10), 1);
-        // At 0 and 100: no fee
-        assert_eq!(kalshi_fee_cents(0), 0);
-        assert_eq!(kalshi_fee_cents(100), 0);
+    fn test_quantity_conversions_are_checked() {
+        assert_eq!(
+            Contracts::from_shares(1_000_000.9),
+            Some(Contracts(1_000_000))
+        );
+        assert_eq!(Contracts::from_shares(-1.0), None);
+        assert_eq!(Contracts::from_shares(f64::NAN), None);
+        assert_eq!(Contracts::from_shares(1e30), None);
+
+        assert_eq!(
+            Contracts(70_000).notional(Price::from_cents(99)),
+            Some(Notional::from_cents(6_930_000))
+        );
+        assert_eq!(Contracts(u64::MAX).notional(Price::CENT), None);
+        assert_eq!(Notional::from_cents(-1250).to_string(), "-$12.50");
     }
 
     #[test]
     fn test_price_conversion() {
-        assert_eq!(price_to_cents(0.50), 50);
-        assert_eq!(price_to_cents(0.01), 1);
-        assert_eq!(price_to_cents(0.99), 99);
-        assert!((cents_to_price(50) - 0.50).abs() < 0.001);
+        assert_eq!(Price::from_dollars(0.50), Some(Price::from_cents(50)));
+        assert_eq!(Price::from_dollars(0.01), Some(Price::from_cents(1)));
+        assert_eq!(Price::from_dollars(0.99), Some(Price::from_cents(99)));
+        assert!((Price::from_cents(50).dollars() - 0.50).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_sub_cent_prices_are_exact() {
+        assert_eq!(Price::parse_decimal("0.523"), Some(Price(5230)));
+        assert_eq!(Price::parse_decimal("0.0015"), Some(Price(15)));
+        assert_eq!(Price::parse_decimal("1"), Some(Price::ONE));
+        assert_eq!(Price::parse_decimal(".999000"), Some(Price(9990)));
+        assert_eq!(Price::parse_decimal("0.00001"), None);
+        assert_eq!(Price::parse_decimal("-0.5"), None);
+        assert_eq!(Price::parse_decimal("."), None);
+
+        // 0.999 no longer collapses into 99¢
+        let tick = Price::parse_decimal("0.001").unwrap();
+        assert_eq!(Price(9990).to_string(), "99.9¢");
+        assert!(Price(9990).is_on_tick(tick) && !Price(9990).is_on_tick(Price::CENT));
+        assert_eq!(Price(5231).ceil_to(Price::CENT), Price::from_cents(53));
+        assert_eq!(Price(5239).floor_to(tick), Price(5230));
     }
 }
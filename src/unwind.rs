@@ -0,0 +1,359 @@
+//! Unwinding legs left unhedged when the other leg of an arb fails.
+//!
+//! When the first leg of an arb fills and the second is rejected, comes back
+//! short or never answers, the filled contracts are a naked directional
+//! position. [`unwind`] works it off in order:
+//!
+//! 1. retry the missing leg, paying up to `retry_tolerance` over its limit
+//!    as long as the retried pair loses no more than selling back would;
+//! 2. sell the filled leg back, accepting down to `sell_tolerance` under
+//!    what it cost;
+//! 3. escalate with an alert, leaving what is still naked to a human.
+//!
+//! What the unwind made or lost is reported against the originating
+//! opportunity.
+
+use tracing::{error, info, warn};
+
+use crate::config::{
+    unwind_max_retries, unwind_retry_tolerance_cents, unwind_sell_tolerance_cents, PAIR_PAYOUT,
+};
+use crate::engine::{cost_of, ExecutionEngine, ExecutionReport};
+use crate::fees::FeeModel;
+use crate::lifecycle::OpportunityKey;
+use crate::orders::{Order, OrderAction, TimeInForce};
+use crate::types::{ArbOpportunity, Contracts, Leg, Notional, Opportunity, Price, Side};
+
+/// How hard to try before giving up on a naked leg
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnwindPolicy {
+    /// Most to pay over the missing leg's original limit
+    pub retry_tolerance: Price,
+    /// Attempts at the missing leg before selling back
+    pub max_retries: u32,
+    /// Most to give up under the filled leg's average price when selling
+    pub sell_tolerance: Price,
+}
+
+impl UnwindPolicy {
+    /// Policy from configuration
+    pub fn from_config() -> Self {
+        Self {
+            retry_tolerance: Price::from_cents(unwind_retry_tolerance_cents()),
+            max_retries: unwind_max_retries(),
+            sell_tolerance: Price::from_cents(unwind_sell_tolerance_cents()),
+        }
+    }
+}
+
+/// What an unwind did with one opportunity's naked contracts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnwindReport {
+    pub opportunity: OpportunityKey,
+    /// First-leg contracts filled, late fills on a cancelled leg included
+    pub filled: Contracts,
+    /// First-leg contracts the second leg did not cover
+    pub naked: Contracts,
+    /// Hedged by retrying the missing leg
+    pub retried: Contracts,
+    /// Sold back on the filled leg
+    pub sold_back: Contracts,
+    /// Still naked; escalated when non-zero
+    pub remaining: Contracts,
+    /// Profit of the retried pairs plus proceeds of the sold-back contracts,
+    /// minus what their first leg cost
+    pub pnl: Notional,
+}
+
+impl UnwindReport {
+    pub fn escalated(&self) -> bool {
+        !self.remaining.is_zero()
+    }
+}
+
+/// `total` spread over each filled contract of `order`
+fn per_contract(order: &Order, total: Notional) -> Price {
+    let units = total.0 / order.filled.get().max(1) as i64;
+    Price(u32::try_from(units).unwrap_or(u32::MAX))
+}
+
+/// Average price paid per filled contract of `order`
+fn average_price(order: &Order) -> Price {
+    per_contract(order, order.cost)
+}
+
+/// Work off whatever `report` left unhedged. Legs still open (no answer in
+/// time) are cancelled first, so late fills are counted before retrying.
+/// Returns `None` when nothing was left naked.
+pub async fn unwind(
+    engine: &ExecutionEngine,
+    policy: &UnwindPolicy,
+    arb: &ArbOpportunity,
+    report: &ExecutionReport,
+) -> Option<UnwindReport> {
+    let mut first = report.first.clone();
+    if first.state.is_open() {
+        first = engine.cancel(first).await;
+    }
+    let mut second = report.second.clone();
+    if let Some(order) = second.take() {
+        second = Some(if order.state.is_open() {
+            engine.cancel(order).await
+        } else {
+            order
+        });
+    }
+
+    let covered = second.as_ref().map_or(Contracts::ZERO, |s| s.filled);
+    let naked = first.filled.saturating_sub(covered);
+    if naked.is_zero() {
+        return None;
+    }
+    let opportunity = Opportunity::Pair(arb.clone()).key();
+    warn!(
+        "[UNWIND] {} | {} contracts of {} naked",
+        opportunity, naked, first.leg
+    );
+
+    let (missing, missing_limit, missing_tick, first_tick) = match first.leg.outcome {
+        Side::Yes => (&arb.no_leg, arb.no_limit, arb.no_tick, arb.yes_tick),
+        Side::No => (&arb.yes_leg, arb.yes_limit, arb.yes_tick, arb.no_tick),
+    };
+    let order = |leg: &Leg, action, price, quantity| {
+        let tif = TimeInForce::ImmediateOrCancel;
+        engine
+            .orders()
+            .create(&arb.market_id, leg.clone(), action, price, quantity, tif)
+    };
+
+    let mut remaining = naked;
+    let mut pnl = Notional::ZERO;
+
+    // 1. Retry the missing leg within the tolerance, below $1
+    let mut retried = Contracts::ZERO;
+    let retry_limit = (missing_limit + policy.retry_tolerance)
+        .min(PAIR_PAYOUT.saturating_sub(missing_tick))
+        .floor_to(missing_tick);
+    // A retried pair may lose no more than selling back would
+    let retry_fee = arb.fees.venue(missing.venue).unit_fee(retry_limit);
+    let pair_cost = per_contract(&first, first.total_cost()) + retry_limit + retry_fee;
+    let worth_retrying = pair_cost <= PAIR_PAYOUT + policy.sell_tolerance;
+    if !worth_retrying {
+        warn!(
+            "[UNWIND] {} | not retrying {} at {}: pairs would cost {}",
+            opportunity, missing, retry_limit, pair_cost
+        );
+    }
+    for _ in 0..policy.max_retries {
+        if remaining.is_zero() || !worth_retrying {
+            break;
+        }
+        let retry = order(missing, OrderAction::Buy, retry_limit, remaining);
+        let retry = engine.send(retry).await;
+        if retry.filled.is_zero() {
+            continue;
+        }
+        let payout = retry.filled.notional(PAIR_PAYOUT).unwrap_or(Notional::ZERO);
+        pnl = pnl
+            .saturating_add(payout)
+            .saturating_sub(cost_of(&first, retry.filled))
+            .saturating_sub(retry.total_cost());
+        retried = retried.saturating_add(retry.filled);
+        remaining = remaining.saturating_sub(retry.filled);
+    }
+
+    // 2. Sell the filled leg back
+    let mut sold_back = Contracts::ZERO;
+    if !remaining.is_zero() {
+        // The average of a fill across levels can fall between ticks; round
+        // up so the sale never goes below the floor
+        let floor = average_price(&first)
+            .saturating_sub(policy.sell_tolerance)
+            .ceil_to(first_tick);
+        let sale = order(&first.leg, OrderAction::Sell, floor, remaining);
+        let sale = engine.send(sale).await;
+        // A sale's total cost is its (negative) net proceeds
+        pnl = pnl
+            .saturating_sub(sale.total_cost())
+            .saturating_sub(cost_of(&first, sale.filled));
+        sold_back = sale.filled;
+        remaining = remaining.saturating_sub(sale.filled);
+    }
+
+    let unwound = UnwindReport {
+        opportunity,
+        filled: first.filled,
+        naked,
+        retried,
+        sold_back,
+        remaining,
+        pnl,
+    };
+
+    // 3. Escalate
+    if unwound.escalated() {
+        error!(
+            "🚨 [UNWIND] {} | {} contracts of {} still naked after {} retries and a sell-back; manual action needed",
+            unwound.opportunity, remaining, first.leg, policy.max_retries
+        );
+    } else {
+        info!(
+            "[UNWIND] {} | retried {} | sold back {} | P&L {}",
+            unwound.opportunity, retried, sold_back, pnl
+        );
+    }
+    Some(unwound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::tests::{arb, engine, MockVenue};
+    use crate::orders::OrderEvent;
+    use crate::types::Venue;
+    use std::sync::Arc;
+
+    fn policy() -> UnwindPolicy {
+        UnwindPolicy {
+            retry_tolerance: Price::from_cents(1),
+            max_retries: 1,
+            sell_tolerance: Price::from_cents(3),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_naked_leg_is_retried_then_sold_back() {
+        // Kalshi YES fills 10; Polymarket NO fills 4, then 2 more on retry
+        let kalshi = Arc::new(MockVenue::new(Venue::Kalshi, 20));
+        let poly = Arc::new(MockVenue::new(Venue::Polymarket, 4));
+        let engine = engine(&kalshi, &poly);
+        let arb = arb(10);
+        let report = engine.execute(&arb, Contracts(10)).await;
+        assert_eq!(report.hedged, Contracts(4));
+
+        *poly.available.lock().unwrap() = Contracts(2);
+        let unwound = unwind(&engine, &policy(), &arb, &report).await.unwrap();
+        let orders = poly.orders.lock().unwrap();
+        assert_eq!(orders[1].price, Price::from_cents(56));
+        assert_eq!(unwound.retried, Contracts(2));
+        assert_eq!(unwound.filled, Contracts(10));
+
+        // The other 4 are sold back on Kalshi at no less than 37¢
+        let sale = kalshi.orders.lock().unwrap()[1].clone();
+        assert_eq!(
+            (sale.action, sale.price),
+            (OrderAction::Sell, Price::from_cents(37))
+        );
+        assert_eq!(unwound.sold_back, Contracts(4));
+        assert!(!unwound.escalated());
+        // Retried pairs: 2 × (100 - 40 - 56); sold back: 4 × (37 - 40)
+        assert_eq!(unwound.pnl, Notional::from_cents(2 * 4 - 4 * 3));
+        assert_eq!(unwound.opportunity, Opportunity::Pair(arb.clone()).key());
+    }
+
+    #[tokio::test]
+    async fn test_unsellable_leg_is_escalated() {
+        let kalshi = Arc::new(MockVenue::new(Venue::Kalshi, 10));
+        let poly = Arc::new(MockVenue {
+            reject: Some("market closed"),
+            ..MockVenue::new(Venue::Polymarket, 100)
+        });
+        let engine = engine(&kalshi, &poly);
+        let arb = arb(10);
+        let report = engine.execute(&arb, Contracts(10)).await;
+
+        // Kalshi's book is used up: nothing to sell into either
+        let unwound = unwind(&engine, &policy(), &arb, &report).await.unwrap();
+        assert_eq!(
+            (unwound.retried, unwound.sold_back),
+            (Contracts::ZERO, Contracts::ZERO)
+        );
+        assert!(unwound.escalated());
+        assert_eq!(unwound.remaining, Contracts(10));
+        assert_eq!(unwound.pnl, Notional::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_sell_back_floor_is_on_tick() {
+        let kalshi = Arc::new(MockVenue::new(Venue::Kalshi, 100));
+        let poly = Arc::new(MockVenue {
+            reject: Some("market closed"),
+            ..MockVenue::new(Venue::Polymarket, 100)
+        });
+        let engine = engine(&kalshi, &poly);
+        let arb = arb(10);
+
+        // Kalshi YES fills across two levels: 3 at 40¢ and 4 at 41¢
+        let first = engine.orders().create(
+            &arb.market_id,
+            arb.yes_leg.clone(),
+            OrderAction::Buy,
+            Price::from_cents(41),
+            Contracts(7),
+            TimeInForce::ImmediateOrCancel,
+        );
+        let id = first.client_order_id.clone();
+        let mut first = engine.orders().apply(&id, OrderEvent::Sent).unwrap();
+        for (quantity, cents) in [(3, 40), (4, 41)] {
+            let fill = OrderEvent::Fill {
+                quantity: Contracts(quantity),
+                price: Price::from_cents(cents),
+                fee: Notional::ZERO,
+            };
+            first = engine.orders().apply(&id, fill).unwrap();
+        }
+        let report = ExecutionReport {
+            market_id: arb.market_id.clone(),
+            first,
+            second: None,
+            hedged: Contracts::ZERO,
+        };
+
+        // Averaging 40.57¢ less 3¢ is 37.57¢, sold at no less than 38¢
+        let unwound = unwind(&engine, &policy(), &arb, &report).await.unwrap();
+        let sale = kalshi.orders.lock().unwrap()[0].clone();
+        assert_eq!(sale.price, Price::from_cents(38));
+        assert_eq!(unwound.sold_back, Contracts(7));
+    }
+
+    #[tokio::test]
+    async fn test_retry_limit_stays_below_a_dollar() {
+        let kalshi = Arc::new(MockVenue::new(Venue::Kalshi, 10));
+        let poly = Arc::new(MockVenue::new(Venue::Polymarket, 0));
+        let engine = engine(&kalshi, &poly);
+        let mut arb = arb(10);
+        arb.yes_limit = Price::from_cents(1);
+        arb.no_limit = Price::from_cents(95);
+        let report = engine.execute(&arb, Contracts(10)).await;
+
+        // 95¢ + 10¢ is capped at 99¢, still within a 3¢ loss on the pair
+        let policy = UnwindPolicy {
+            retry_tolerance: Price::from_cents(10),
+            ..policy()
+        };
+        *poly.available.lock().unwrap() = Contracts(10);
+        let unwound = unwind(&engine, &policy, &arb, &report).await.unwrap();
+        assert_eq!(poly.orders.lock().unwrap()[1].price, Price::from_cents(99));
+        assert_eq!(unwound.retried, Contracts(10));
+    }
+
+    #[tokio::test]
+    async fn test_losing_retry_is_skipped_for_a_sell_back() {
+        let kalshi = Arc::new(MockVenue::new(Venue::Kalshi, 20));
+        let poly = Arc::new(MockVenue::new(Venue::Polymarket, 0));
+        let engine = engine(&kalshi, &poly);
+        let arb = arb(10);
+        let report = engine.execute(&arb, Contracts(10)).await;
+
+        // 40¢ + (55¢ + 10¢) would lose 5¢ a pair; selling back loses at most 3¢
+        let policy = UnwindPolicy {
+            retry_tolerance: Price::from_cents(10),
+            ..policy()
+        };
+        *poly.available.lock().unwrap() = Contracts(10);
+        let unwound = unwind(&engine, &policy, &arb, &report).await.unwrap();
+        assert_eq!(poly.orders.lock().unwrap().len(), 1);
+        assert_eq!(unwound.retried, Contracts::ZERO);
+        assert_eq!(unwound.sold_back, Contracts(10));
+    }
+}